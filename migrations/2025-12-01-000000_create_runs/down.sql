@@ -0,0 +1,3 @@
+-- Down.sql
+ALTER TABLE assignments DROP COLUMN run_id;
+DROP TABLE runs;
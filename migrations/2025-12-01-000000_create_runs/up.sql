@@ -0,0 +1,10 @@
+-- Up.sql
+CREATE TABLE runs (
+    id SERIAL PRIMARY KEY,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    attempts INTEGER NOT NULL,
+    seed BIGINT,
+    forced BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+ALTER TABLE assignments ADD COLUMN run_id INTEGER REFERENCES runs(id);
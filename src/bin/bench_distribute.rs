@@ -0,0 +1,224 @@
+//! Stress benchmark for the distribution algorithm.
+//!
+//! Generates synthetic people, tasks and history at a configurable scale, then times
+//! repeated distributions and prints one JSON object with the results. Every successful
+//! distribution is checked for correctness, so the benchmark doubles as a smoke test.
+//!
+//! `--seed` drives both the synthetic history and every distribution, so the same options give
+//! the same success rate and attempt counts; only the timings vary. Only the most recent
+//! `--history-window` cycles of history are generated, as the generator never looks further back,
+//! so `--cycles` above the window has no effect (`history_per_person` in the output).
+//!
+//! ```text
+//! cargo run --release --bin bench_distribute -- --people 500 --tasks 20 --cycles 50 --runs 100
+//! ```
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Instant;
+use work_group_generator::group::{self, DistributionRules, GroupMembers};
+
+/// Tasks kept in place of the first synthetic ones so the A/B exclusions apply
+const RESTRICTED_TASKS: [&str; 2] = ["Toilet A", "Toilet B"];
+
+struct Options {
+    people: usize,
+    tasks: usize,
+    cycles: usize,
+    history_window: usize,
+    runs: u32,
+    max_attempts: u32,
+    seed: u64,
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut options = Options {
+            people: 500,
+            tasks: 20,
+            cycles: 50,
+            // Same default as the `history_window` setting
+            history_window: 5,
+            runs: 100,
+            max_attempts: 500,
+            seed: 42,
+        };
+
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        for pair in args.chunks(2) {
+            let [flag, value] = pair else {
+                return Err(format!("missing value for '{}'", pair[0]));
+            };
+            let parse_err = |_| format!("invalid value '{}' for '{}'", value, flag);
+            match flag.as_str() {
+                "--people" => options.people = value.parse().map_err(parse_err)?,
+                "--tasks" => options.tasks = value.parse().map_err(parse_err)?,
+                "--cycles" => options.cycles = value.parse().map_err(parse_err)?,
+                "--history-window" => options.history_window = value.parse().map_err(parse_err)?,
+                "--runs" => options.runs = value.parse().map_err(parse_err)?,
+                "--max-attempts" => options.max_attempts = value.parse().map_err(parse_err)?,
+                "--seed" => options.seed = value.parse().map_err(parse_err)?,
+                _ => return Err(format!("unknown option '{}'", flag)),
+            }
+        }
+
+        if options.tasks < RESTRICTED_TASKS.len() || options.people < options.tasks {
+            return Err("need at least 2 tasks and at least one person per task".to_string());
+        }
+        if options.history_window == 0 {
+            return Err("history window must be at least 1".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// Splits people evenly between groups A and B
+fn synthetic_groups(people: usize) -> GroupMembers {
+    let names: Vec<String> = (1..=people).map(|i| format!("Person {}", i)).collect();
+    let (a, b) = names.split_at(people / 2);
+    group::two_groups(a, b)
+}
+
+/// Spreads roughly 80% of the people across the tasks, at least one spot each
+fn synthetic_tasks(people: usize, tasks: usize) -> HashMap<String, usize> {
+    let spots = (people * 4 / 5).max(tasks);
+    (0..tasks)
+        .map(|i| {
+            let name = match RESTRICTED_TASKS.get(i) {
+                Some(task) => task.to_string(),
+                None => format!("Task {}", i + 1),
+            };
+            let count = spots / tasks + usize::from(i < spots % tasks);
+            (name, count)
+        })
+        .collect()
+}
+
+/// Random past tasks per person, most recent first, truncated to the history window.
+/// People and tasks are visited in name order so the seed alone decides the result.
+fn synthetic_history(
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    cycles: usize,
+    history_window: usize,
+    rng: &mut StdRng,
+) -> HashMap<String, Vec<String>> {
+    let mut tasks: Vec<&String> = work_areas.keys().collect();
+    tasks.sort();
+    let mut people: Vec<&String> = groups.values().flatten().collect();
+    people.sort();
+    people
+        .into_iter()
+        .map(|person| {
+            let recent = (0..cycles.min(history_window))
+                .map(|_| tasks.choose(rng).unwrap().to_string())
+                .collect();
+            (person.clone(), recent)
+        })
+        .collect()
+}
+
+/// Panics if a distribution leaves a task unfilled, reuses someone, or breaks a group exclusion
+fn assert_valid(
+    assignments: &HashMap<String, Vec<String>>,
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    constraints: &HashMap<String, Vec<String>>,
+) {
+    let mut seen = HashMap::new();
+    for (task, people) in assignments {
+        assert_eq!(people.len(), work_areas[task], "task '{}' not filled", task);
+        for person in people {
+            *seen.entry(person).or_insert(0) += 1;
+            for (group, members) in groups {
+                let excluded = constraints.get(group).is_some_and(|t| t.contains(task));
+                assert!(
+                    !(excluded && members.contains(person)),
+                    "'{}' from group {} placed on '{}'",
+                    person,
+                    group,
+                    task
+                );
+            }
+        }
+    }
+    assert!(
+        seen.values().all(|&count| count == 1),
+        "someone was given more than one task"
+    );
+}
+
+fn main() {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("bench_distribute: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let groups = synthetic_groups(options.people);
+    let work_areas = synthetic_tasks(options.people, options.tasks);
+    let history = synthetic_history(
+        &groups,
+        &work_areas,
+        options.cycles,
+        options.history_window,
+        &mut rng,
+    );
+    let constraints = group::group_constraints();
+    let rules = DistributionRules::default();
+
+    let mut successes = 0u32;
+    let mut total_attempts = 0u64;
+    let mut run_ms = Vec::new();
+    let started = Instant::now();
+
+    for _ in 0..options.runs {
+        let run_started = Instant::now();
+        let result = group::distribute_with_retries_with_rng(
+            &groups,
+            &work_areas,
+            &constraints,
+            &history,
+            &rules,
+            options.max_attempts,
+            &mut rng,
+        );
+        run_ms.push(run_started.elapsed().as_secs_f64() * 1000.0);
+
+        match result {
+            Some((attempts, assignments)) => {
+                assert_valid(&assignments, &groups, &work_areas, &constraints);
+                successes += 1;
+                total_attempts += u64::from(attempts);
+            }
+            None => total_attempts += u64::from(options.max_attempts),
+        }
+    }
+
+    let total_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let runs = f64::from(options.runs.max(1));
+    println!(
+        "{}",
+        json!({
+            "people": options.people,
+            "tasks": options.tasks,
+            "cycles": options.cycles,
+            "history_window": options.history_window,
+            "history_per_person": options.cycles.min(options.history_window),
+            "runs": options.runs,
+            "max_attempts": options.max_attempts,
+            "seed": options.seed,
+            "success_rate": f64::from(successes) / runs,
+            "avg_attempts": total_attempts as f64 / runs,
+            "total_ms": total_ms,
+            "avg_run_ms": total_ms / runs,
+            "max_run_ms": run_ms.iter().cloned().fold(0.0, f64::max),
+        })
+    );
+}
@@ -1,12 +1,64 @@
+use crate::group::Lock;
 use config::{Config, ConfigError, File};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Where the task -> required people map lives
+pub const TASKS_CONFIG_PATH: &str = "config/tasks.toml";
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub database_url: String,
+    /// Task name -> number of people required, resolved by `resolve_work_assignments`
+    #[serde(skip)]
     pub work_assignments: HashMap<String, usize>,
     pub github_env_path: Option<String>,
+    /// People pinned to specific tasks for the next run
+    #[serde(default)]
+    pub locks: Vec<Lock>,
+    /// Pairs of people who must never share a task
+    #[serde(default)]
+    pub conflicts: Vec<[String; 2]>,
+    /// Task -> group -> fewest members of that group the task must get
+    #[serde(default)]
+    pub min_from_group: HashMap<String, HashMap<String, usize>>,
+    /// Task -> group -> most members of that group the task may get
+    #[serde(default)]
+    pub max_from_group: HashMap<String, HashMap<String, usize>>,
+    /// Most tasks any one person may receive in a cycle
+    #[serde(default = "default_max_per_person")]
+    pub max_per_person: usize,
+    /// Tasks every person is guaranteed in a cycle when capacity allows
+    #[serde(default)]
+    pub min_per_person: usize,
+    /// Days before someone may repeat the same task (0 disables the check)
+    #[serde(default)]
+    pub min_days_between_same_task: i64,
+    /// Let back in whoever did a task longest ago if the gap would leave it short
+    #[serde(default)]
+    pub relax_same_task_gap: bool,
+    /// Past tasks per person the rotation check looks back over
+    #[serde(default = "default_history_window")]
+    pub history_window: usize,
+    /// Shuffled attempts at a distribution before giving up
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Database connection attempts before giving up
+    #[serde(default = "default_db_connect_attempts")]
+    pub db_connect_attempts: u32,
+    /// Delay before the first reconnection, doubled after each failure
+    #[serde(default = "default_db_connect_base_delay_ms")]
+    pub db_connect_base_delay_ms: u64,
+    /// Most connections the pool may open
+    #[serde(default = "default_db_pool_max_size")]
+    pub db_pool_max_size: u32,
+    /// Seconds to wait for a pooled connection before failing
+    #[serde(default = "default_db_pool_timeout_secs")]
+    pub db_pool_timeout_secs: u64,
+    /// Where to write an iCalendar feed of each new distribution (skipped when unset)
+    pub calendar_path: Option<String>,
 }
 
 impl Settings {
@@ -19,12 +71,240 @@ impl Settings {
             // Add environment specific config (e.g. config/production.toml)
             .add_source(File::with_name(&format!("config/{}", run_mode)).required(false))
             // Add environment overrides
-            // e.g. APP_DATABASE_URL=postgres://...
+            // e.g. APP__MAX_ATTEMPTS=1000 (the prefix takes the "__" separator too)
             .add_source(config::Environment::with_prefix("APP").separator("__"))
             .set_override_option("database_url", std::env::var("DATABASE_URL").ok())?
             .set_override_option("github_env_path", std::env::var("GITHUB_ENV").ok())?
             .build()?;
 
-        s.try_deserialize()
+        let work_assignments = resolve_work_assignments(&s, TASKS_CONFIG_PATH)?;
+        let mut settings: Settings = s.try_deserialize()?;
+        validate_history_window(settings.history_window)?;
+        settings.work_assignments = work_assignments;
+        Ok(settings)
+    }
+}
+
+fn default_max_per_person() -> usize {
+    1
+}
+
+fn default_history_window() -> usize {
+    5
+}
+
+fn default_max_attempts() -> u32 {
+    500
+}
+
+fn default_db_connect_attempts() -> u32 {
+    5
+}
+
+fn default_db_connect_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_db_pool_max_size() -> u32 {
+    2
+}
+
+fn default_db_pool_timeout_secs() -> u64 {
+    30
+}
+
+/// The household's current tasks, used when no tasks file is present.
+pub fn default_work_assignments() -> HashMap<String, usize> {
+    HashMap::from([
+        ("Parlor".to_string(), 5),
+        ("Frontyard".to_string(), 3),
+        ("Backyard".to_string(), 1),
+        ("Tank".to_string(), 2),
+        ("Toilet B".to_string(), 4),
+        ("Toilet A".to_string(), 2),
+        ("Bin".to_string(), 1),
+    ])
+}
+
+/// Picks the task -> count map for this run and validates it.
+///
+/// A `[work_assignments]` table in the settings sources (`config/default.toml`,
+/// `config/{RUN_MODE}.toml` or `APP__WORK_ASSIGNMENTS__*`) takes precedence, as it did before the
+/// tasks file existed. Otherwise the tasks file at `tasks_path` is used, then the defaults.
+pub fn resolve_work_assignments<P: AsRef<Path>>(
+    settings: &Config,
+    tasks_path: P,
+) -> Result<HashMap<String, usize>, ConfigError> {
+    match settings.get::<HashMap<String, usize>>("work_assignments") {
+        Ok(work_assignments) => {
+            validate_work_assignments(&work_assignments)?;
+            if tasks_path.as_ref().exists() {
+                warn!(
+                    "work_assignments set in settings, ignoring {}",
+                    tasks_path.as_ref().display()
+                );
+            }
+            info!(
+                "Loaded {} work assignments from settings",
+                work_assignments.len()
+            );
+            Ok(work_assignments)
+        }
+        Err(ConfigError::NotFound(_)) => load_work_assignments_from(tasks_path),
+        Err(e) => Err(e),
+    }
+}
+
+/// Loads the `[work_assignments]` table from `config/tasks.toml` and validates it.
+pub fn load_work_assignments() -> Result<HashMap<String, usize>, ConfigError> {
+    load_work_assignments_from(TASKS_CONFIG_PATH)
+}
+
+/// Loads the `[work_assignments]` table from the given TOML file and validates it.
+/// Falls back to `default_work_assignments` when the file does not exist.
+pub fn load_work_assignments_from<P: AsRef<Path>>(
+    path: P,
+) -> Result<HashMap<String, usize>, ConfigError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        warn!(
+            "Tasks file not found at {}, using default work assignments",
+            path.display()
+        );
+        return Ok(default_work_assignments());
+    }
+
+    let s = Config::builder().add_source(File::from(path)).build()?;
+    let work_assignments: HashMap<String, usize> = s.get("work_assignments")?;
+    validate_work_assignments(&work_assignments)?;
+
+    info!(
+        "Loaded {} work assignments from {}",
+        work_assignments.len(),
+        path.display()
+    );
+    Ok(work_assignments)
+}
+
+fn validate_work_assignments(work_assignments: &HashMap<String, usize>) -> Result<(), ConfigError> {
+    if work_assignments.is_empty() {
+        return Err(ConfigError::Message(
+            "work_assignments must define at least one task".to_string(),
+        ));
+    }
+
+    if let Some((task, _)) = work_assignments.iter().find(|(_, &count)| count == 0) {
+        return Err(ConfigError::Message(format!(
+            "Task '{}' must require at least one person",
+            task
+        )));
+    }
+
+    Ok(())
+}
+
+fn validate_history_window(history_window: usize) -> Result<(), ConfigError> {
+    if history_window == 0 {
+        return Err(ConfigError::Message(
+            "history_window must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn fixture(name: &str, content: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("vividshift_{}_{}.toml", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_work_assignments_from_fixture() {
+        let path = fixture("tasks", "[work_assignments]\n\"Parlor\" = 2\n\"Bin\" = 1\n");
+
+        let work_assignments = load_work_assignments_from(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(work_assignments.len(), 2);
+        assert_eq!(work_assignments["Parlor"], 2);
+        assert_eq!(work_assignments["Bin"], 1);
+    }
+
+    #[test]
+    fn test_load_work_assignments_falls_back_when_absent() {
+        let work_assignments = load_work_assignments_from("nonexistent/tasks.toml").unwrap();
+        assert_eq!(work_assignments, default_work_assignments());
+    }
+
+    #[test]
+    fn test_load_work_assignments_reads_shipped_tasks_file() {
+        // Tests run from the crate root, where the shipped config/tasks.toml lives
+        let work_assignments = load_work_assignments().unwrap();
+        assert_eq!(work_assignments, default_work_assignments());
+    }
+
+    fn settings_from(content: &str) -> Config {
+        Config::builder()
+            .add_source(File::from_str(content, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_settings_work_assignments_take_precedence() {
+        let path = fixture("tasks_shadowed", "[work_assignments]\n\"Parlor\" = 2\n");
+        let settings = settings_from("[work_assignments]\n\"Tank\" = 3\n");
+
+        let work_assignments = resolve_work_assignments(&settings, &path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(work_assignments, HashMap::from([("Tank".to_string(), 3)]));
+    }
+
+    #[test]
+    fn test_settings_without_work_assignments_use_tasks_file() {
+        let path = fixture("tasks_fallback", "[work_assignments]\n\"Parlor\" = 2\n");
+        let settings = settings_from("max_attempts = 10\n");
+
+        let work_assignments = resolve_work_assignments(&settings, &path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(work_assignments, HashMap::from([("Parlor".to_string(), 2)]));
+    }
+
+    #[test]
+    fn test_settings_work_assignments_are_validated() {
+        let settings = settings_from("[work_assignments]\n\"Parlor\" = 0\n");
+
+        let result = resolve_work_assignments(&settings, "nonexistent/tasks.toml");
+
+        assert!(result.is_err(), "Zero-count task should be rejected");
+    }
+
+    #[test]
+    fn test_history_window_defaults_and_rejects_zero() {
+        let settings: Settings = settings_from("database_url = \"postgres://localhost/test\"\n")
+            .try_deserialize()
+            .unwrap();
+
+        assert_eq!(settings.history_window, 5);
+        assert!(validate_history_window(settings.history_window).is_ok());
+        assert!(validate_history_window(0).is_err());
+    }
+
+    #[test]
+    fn test_load_work_assignments_rejects_zero_count() {
+        let path = fixture("tasks_zero", "[work_assignments]\n\"Parlor\" = 0\n");
+
+        let result = load_work_assignments_from(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(result.is_err(), "Zero-count task should be rejected");
     }
 }
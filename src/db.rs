@@ -1,29 +1,128 @@
 use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager};
-use std::collections::HashMap;
+use diesel::sql_types::{BigInt, Double, Timestamp};
+use std::collections::{BTreeMap, HashMap};
+use std::thread;
+use std::time::Duration;
 
 use crate::models::*;
 use crate::schema::assignments::dsl as assignments_dsl;
 use crate::schema::people::dsl as people_dsl;
-use tracing::info;
+use crate::schema::runs::dsl as runs_dsl;
+use tracing::{info, warn};
 
 pub type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
-pub fn establish_connection(database_url: &str) -> DbPool {
+/// Active people keyed by group, plus the name-to-id lookup used when saving.
+pub type PeopleByGroup = (HashMap<String, Vec<String>>, HashMap<String, i32>);
+
+/// Metadata describing how a generation run was produced.
+#[derive(Debug, Clone, Default)]
+pub struct RunMeta {
+    /// Attempt on which a valid distribution was found
+    pub attempts: u32,
+    /// RNG seed, if the run was seeded
+    pub seed: Option<i64>,
+    /// Whether the schedule check was bypassed
+    pub forced: bool,
+}
+
+/// How often to retry connecting before giving up, doubling the delay each time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total connection attempts, including the first
+    pub attempts: u32,
+    /// Delay before the second attempt
+    pub base_delay: Duration,
+}
+
+/// A single past generation run: every assignment saved with the same timestamp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleRecord {
+    pub assigned_at: NaiveDateTime,
+    /// Task name -> people assigned to it in this cycle
+    pub tasks: HashMap<String, Vec<String>>,
+}
+
+/// How evenly one cycle's assignments were spread across active people.
+#[derive(QueryableByName, Debug, Clone, PartialEq)]
+pub struct CycleFairness {
+    #[diesel(sql_type = Timestamp)]
+    pub assigned_at: NaiveDateTime,
+    /// Active people considered, including those with no assignment that cycle
+    #[diesel(sql_type = BigInt)]
+    pub people: i64,
+    #[diesel(sql_type = BigInt)]
+    pub assignments: i64,
+    /// Population variance of per-person assignment counts; 0 means perfectly even
+    #[diesel(sql_type = Double)]
+    pub variance: f64,
+}
+
+/// A person together with the date of their most recent assignment.
+#[derive(Debug, Clone)]
+pub struct PersonWithStats {
+    pub person: Person,
+    /// `None` if the person has never been assigned
+    pub last_assigned_at: Option<NaiveDateTime>,
+}
+
+/// Builds a connection pool holding at most `max_size` connections.
+/// Both building the pool and checking out a connection fail after `timeout`.
+pub fn establish_pool(
+    database_url: &str,
+    max_size: u32,
+    timeout: Duration,
+) -> Result<DbPool, r2d2::PoolError> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     r2d2::Pool::builder()
+        .max_size(max_size)
+        .connection_timeout(timeout)
         .build(manager)
-        .expect("Failed to create pool.")
+}
+
+/// Builds a pool with `establish_pool`, retrying per `policy` while the database is unreachable.
+pub fn establish_connection(
+    database_url: &str,
+    max_size: u32,
+    timeout: Duration,
+    policy: &RetryPolicy,
+) -> Result<DbPool, r2d2::PoolError> {
+    retry_with_backoff(policy, || establish_pool(database_url, max_size, timeout))
+}
+
+/// Runs `op` until it succeeds or `policy.attempts` is exhausted, sleeping
+/// `base_delay * 2^n` between tries. Returns the last error on failure.
+fn retry_with_backoff<T, E, F>(policy: &RetryPolicy, mut op: F) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Result<T, E>,
+{
+    let attempts = policy.attempts.max(1);
+    let mut delay = policy.base_delay;
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < attempts => {
+                warn!(
+                    "Database connection attempt {}/{} failed: {}. Retrying in {:?}...",
+                    attempt, attempts, e, delay
+                );
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
 }
 
 /// Fetches all active people from the database, separated by group.
 /// Uses people.toml as the source of truth for group membership and active status.
-pub fn fetch_people(
-    conn: &mut PgConnection,
-) -> QueryResult<(Vec<String>, Vec<String>, HashMap<String, i32>)> {
+pub fn fetch_people(conn: &mut PgConnection) -> QueryResult<PeopleByGroup> {
     use crate::people_config::PeopleConfiguration;
-    use tracing::warn;
 
     // Load configuration from people.toml
     let config = PeopleConfiguration::load().map_err(|e| {
@@ -36,8 +135,10 @@ pub fn fetch_people(
     // Fetch all people from database to get their IDs
     let all_db_people = people_dsl::people.load::<Person>(conn)?;
 
-    let mut names_a = Vec::new();
-    let mut names_b = Vec::new();
+    let mut groups: HashMap<String, Vec<String>> = config
+        .get_group_ids()
+        .map(|id| (id.clone(), Vec::new()))
+        .collect();
     let mut name_to_id = HashMap::new();
 
     // Build name-to-id mapping from database
@@ -51,15 +152,12 @@ pub fn fetch_people(
         if let Some(&person_id) = db_name_to_id.get(&person_config.name) {
             name_to_id.insert(person_config.name.clone(), person_id);
 
-            if person_config.group == "A" {
-                names_a.push(person_config.name.clone());
-            } else if person_config.group == "B" {
-                names_b.push(person_config.name.clone());
-            } else {
-                warn!(
-                    "Person '{}' has unknown group '{}', skipping",
-                    person_config.name, person_config.group
-                );
+            // Multi-group people land in every list they belong to
+            for group in &person_config.groups {
+                groups
+                    .entry(group.clone())
+                    .or_default()
+                    .push(person_config.name.clone());
             }
         } else {
             warn!(
@@ -70,48 +168,184 @@ pub fn fetch_people(
     }
 
     info!(
-        "Loaded {} people from config (Group A: {}, Group B: {})",
-        names_a.len() + names_b.len(),
-        names_a.len(),
-        names_b.len()
+        "Loaded {} people from config ({})",
+        name_to_id.len(),
+        group_counts(&groups)
     );
 
-    Ok((names_a, names_b, name_to_id))
+    Ok((groups, name_to_id))
+}
+
+/// Formats member counts per group, sorted by group, e.g. "A: 8, B: 10".
+pub fn group_counts(groups: &HashMap<String, Vec<String>>) -> String {
+    let mut ids: Vec<&String> = groups.keys().collect();
+    ids.sort();
+    ids.iter()
+        .map(|id| format!("{}: {}", id, groups[*id].len()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Lists every person with their most recent `assigned_at`, ordered by name.
+pub fn fetch_people_with_last_assignment(
+    conn: &mut PgConnection,
+) -> QueryResult<Vec<PersonWithStats>> {
+    use crate::schema::{assignments, people};
+    use diesel::dsl::max;
+
+    let rows = people::table
+        .left_join(assignments::table)
+        .group_by(people::id)
+        .select((
+            Person::as_select(),
+            max(assignments::assigned_at.nullable()),
+        ))
+        .order(people::name.asc())
+        .load::<(Person, Option<NaiveDateTime>)>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(person, last_assigned_at)| PersonWithStats {
+            person,
+            last_assigned_at,
+        })
+        .collect())
 }
 
 /// Fetches the recent history for all people.
-/// Returns a HashMap where key is person's name and value is list of recent tasks.
+/// Returns a HashMap where key is person's name and value is list of their last `window` tasks.
+/// Callers that also need the cycles should use `fetch_history_detailed` and `recent_history`.
 pub fn fetch_history(
     conn: &mut PgConnection,
     name_to_id: &HashMap<String, i32>,
+    window: usize,
 ) -> QueryResult<HashMap<String, Vec<String>>> {
-    let mut history_map = HashMap::new();
-
-    // Get all assignments, ordered by date desc
-    // Ideally we'd limit this per person, but for simplicity we can fetch recent ones globally
-    // or just fetch all and filter in memory if the dataset is small.
-    // Given the context, let's fetch the last 50 assignments per person roughly.
-    // Actually, let's just fetch all assignments for now as the dataset seems small (household chores).
+    Ok(recent_history(
+        &fetch_history_detailed(conn, name_to_id)?,
+        window,
+    ))
+}
 
+/// Fetches the full assignment history grouped into cycles, ordered oldest-to-newest.
+/// Nothing is truncated, so callers can reason about dates and per-task detail;
+/// `recent_history` derives the truncated per-person view from the result.
+pub fn fetch_history_detailed(
+    conn: &mut PgConnection,
+    name_to_id: &HashMap<String, i32>,
+) -> QueryResult<Vec<CycleRecord>> {
     let all_assignments = assignments_dsl::assignments
-        .order(assignments_dsl::assigned_at.desc())
+        .order((
+            assignments_dsl::assigned_at.asc(),
+            assignments_dsl::id.asc(),
+        ))
         .load::<Assignment>(conn)?;
 
-    // Create a reverse lookup for IDs to Names
     let id_to_name: HashMap<i32, String> =
         name_to_id.iter().map(|(n, i)| (*i, n.clone())).collect();
 
-    for assignment in all_assignments {
-        if let Some(name) = id_to_name.get(&assignment.person_id) {
-            let entry = history_map.entry(name.clone()).or_insert_with(Vec::new);
-            // We only care about the last few assignments for the logic
-            if entry.len() < 5 {
-                entry.push(assignment.task_name);
+    Ok(group_into_cycles(&all_assignments, &id_to_name))
+}
+
+/// Groups assignment rows into cycles keyed by their `assigned_at` timestamp, oldest first.
+/// `save_assignments` stamps every row of a run with the same time, so equal timestamps form one
+/// cycle wherever they appear in the input. Rows for people not in `id_to_name` (e.g. inactive)
+/// are ignored.
+fn group_into_cycles(
+    assignments: &[Assignment],
+    id_to_name: &HashMap<i32, String>,
+) -> Vec<CycleRecord> {
+    let mut cycles: BTreeMap<NaiveDateTime, HashMap<String, Vec<String>>> = BTreeMap::new();
+
+    for assignment in assignments {
+        let Some(name) = id_to_name.get(&assignment.person_id) else {
+            continue;
+        };
+
+        cycles
+            .entry(assignment.assigned_at)
+            .or_default()
+            .entry(assignment.task_name.clone())
+            .or_default()
+            .push(name.clone());
+    }
+
+    cycles
+        .into_iter()
+        .map(|(assigned_at, tasks)| CycleRecord { assigned_at, tasks })
+        .collect()
+}
+
+/// Each person's most recent tasks, newest first, keeping at most `window` per person.
+/// Built from cycles ordered oldest-to-newest, so the history is not loaded a second time.
+pub fn recent_history(cycles: &[CycleRecord], window: usize) -> HashMap<String, Vec<String>> {
+    let mut history_map: HashMap<String, Vec<String>> = HashMap::new();
+
+    for cycle in cycles.iter().rev() {
+        let mut tasks: Vec<&String> = cycle.tasks.keys().collect();
+        tasks.sort();
+        for task in tasks {
+            for person in &cycle.tasks[task] {
+                let entry = history_map.entry(person.clone()).or_default();
+                // We only care about the last few assignments for the logic
+                if entry.len() < window {
+                    entry.push(task.clone());
+                }
             }
         }
     }
 
-    Ok(history_map)
+    history_map
+}
+
+/// Per-cycle variance of assignment counts for the last `last_n_cycles` cycles, oldest first.
+/// A rising trend means the rotation is drifting away from an even spread.
+pub fn fetch_fairness_trend(
+    conn: &mut PgConnection,
+    last_n_cycles: usize,
+) -> QueryResult<Vec<CycleFairness>> {
+    let limit = i64::try_from(last_n_cycles).unwrap_or(i64::MAX);
+
+    diesel::sql_query(
+        "WITH recent AS ( \
+             SELECT DISTINCT assigned_at FROM assignments \
+             ORDER BY assigned_at DESC LIMIT $1 \
+         ), counts AS ( \
+             SELECT r.assigned_at, p.id, COUNT(a.id) AS n \
+             FROM recent r \
+             CROSS JOIN people p \
+             LEFT JOIN assignments a \
+                 ON a.assigned_at = r.assigned_at AND a.person_id = p.id \
+             WHERE p.active \
+             GROUP BY r.assigned_at, p.id \
+         ) \
+         SELECT assigned_at, \
+                COUNT(*) AS people, \
+                SUM(n)::int8 AS assignments, \
+                COALESCE(VAR_POP(n), 0)::float8 AS variance \
+         FROM counts \
+         GROUP BY assigned_at \
+         ORDER BY assigned_at ASC",
+    )
+    .bind::<BigInt, _>(limit)
+    .load(conn)
+}
+
+/// Returns when each person last did each task, from cycles ordered oldest-to-newest.
+pub fn last_task_dates(cycles: &[CycleRecord]) -> HashMap<String, HashMap<String, NaiveDateTime>> {
+    let mut last_done: HashMap<String, HashMap<String, NaiveDateTime>> = HashMap::new();
+
+    for cycle in cycles {
+        for (task, people) in &cycle.tasks {
+            for person in people {
+                last_done
+                    .entry(person.clone())
+                    .or_default()
+                    .insert(task.clone(), cycle.assigned_at);
+            }
+        }
+    }
+
+    last_done
 }
 
 /// Checks if it has been 14 days since the last assignment run.
@@ -135,27 +369,456 @@ pub fn should_run(conn: &mut PgConnection) -> QueryResult<bool> {
     }
 }
 
+/// Records a generation run and returns its id.
+pub fn create_run(conn: &mut PgConnection, meta: &RunMeta) -> QueryResult<i32> {
+    let new_run = NewRun {
+        created_at: Utc::now().naive_utc(),
+        attempts: i32::try_from(meta.attempts)
+            .map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))?,
+        seed: meta.seed,
+        forced: meta.forced,
+    };
+
+    diesel::insert_into(runs_dsl::runs)
+        .values(&new_run)
+        .returning(runs_dsl::id)
+        .get_result(conn)
+}
+
+/// Saves a run record and its assignments in one transaction, returning the run id.
 pub fn save_assignments(
     conn: &mut PgConnection,
     assignments: &HashMap<String, Vec<String>>,
     name_to_id: &HashMap<String, i32>,
-) -> QueryResult<()> {
-    let now = Utc::now().naive_utc();
-
-    for (task, people_names) in assignments {
-        for name in people_names {
-            if let Some(&person_id) = name_to_id.get(name) {
-                let new_assign = NewAssignment {
-                    person_id,
-                    task_name: task,
-                    assigned_at: now,
-                };
+    meta: &RunMeta,
+) -> QueryResult<i32> {
+    conn.transaction(|conn| {
+        let run_id = create_run(conn, meta)?;
+        let now = Utc::now().naive_utc();
+
+        for (task, people_names) in assignments {
+            for name in people_names {
+                if let Some(&person_id) = name_to_id.get(name) {
+                    let new_assign = NewAssignment {
+                        person_id,
+                        task_name: task,
+                        assigned_at: now,
+                        run_id: Some(run_id),
+                    };
+
+                    diesel::insert_into(assignments_dsl::assignments)
+                        .values(&new_assign)
+                        .execute(conn)?;
+                }
+            }
+        }
+
+        Ok(run_id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+    }
+
+    fn row(id: i32, person_id: i32, task: &str, day: u32) -> Assignment {
+        Assignment {
+            id,
+            person_id,
+            task_name: task.to_string(),
+            assigned_at: at(day),
+            run_id: None,
+        }
+    }
+
+    #[test]
+    fn test_establish_pool_times_out_on_bad_url() {
+        let timeout = Duration::from_millis(200);
+        let started = std::time::Instant::now();
+
+        let result = establish_pool("postgres://nobody@127.0.0.1:1/missing", 1, timeout);
 
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    /// Needs a scratch database; run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_establish_pool_hands_out_connections() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+
+        let pool = establish_pool(&url, 2, Duration::from_secs(5)).unwrap();
+        let mut conn = pool.get().unwrap();
+
+        assert_eq!(pool.max_size(), 2);
+        assert!(diesel::sql_query("SELECT 1").execute(&mut conn).is_ok());
+    }
+
+    /// Needs a migrated scratch database; run with `cargo test -- --ignored`.
+    /// Everything is rolled back afterwards.
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_save_assignments_links_rows_to_run() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let mut conn = PgConnection::establish(&url).unwrap();
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            let person_id: i32 = diesel::insert_into(people_dsl::people)
+                .values(&NewPerson {
+                    name: "Run Test Person",
+                    group_type: "A",
+                })
+                .returning(people_dsl::id)
+                .get_result(conn)?;
+            let name_to_id = HashMap::from([("Run Test Person".to_string(), person_id)]);
+            let assignments =
+                HashMap::from([("Parlor".to_string(), vec!["Run Test Person".to_string()])]);
+            let meta = RunMeta {
+                attempts: 3,
+                seed: Some(7),
+                forced: true,
+            };
+
+            let run_id = save_assignments(conn, &assignments, &name_to_id, &meta)?;
+
+            let run: Run = runs_dsl::runs.find(run_id).first(conn)?;
+            assert_eq!((run.attempts, run.seed, run.forced), (3, Some(7), true));
+
+            let saved: Vec<Assignment> = assignments_dsl::assignments
+                .filter(assignments_dsl::person_id.eq(person_id))
+                .load(conn)?;
+            assert_eq!(saved.len(), 1);
+            assert_eq!(saved[0].task_name, "Parlor");
+            assert_eq!(saved[0].run_id, Some(run_id));
+            Ok(())
+        });
+    }
+
+    /// Log lines written while `f` runs, captured by a scoped subscriber
+    fn capture_logs<T>(f: impl FnOnce() -> T) -> (T, String) {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Buffer {
+            fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(bytes);
+                Ok(bytes.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let result = tracing::subscriber::with_default(subscriber, f);
+        let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        (result, logs)
+    }
+
+    #[test]
+    fn test_establish_connection_gives_up_on_bad_url() {
+        let policy = RetryPolicy {
+            attempts: 3,
+            base_delay: Duration::from_millis(1),
+        };
+        let timeout = Duration::from_millis(200);
+
+        let (result, logs) = capture_logs(|| {
+            establish_connection("postgres://nobody@127.0.0.1:1/missing", 1, timeout, &policy)
+        });
+
+        // Every attempt but the last logs a retry; the last one's error is returned
+        let retries: Vec<&str> = logs
+            .lines()
+            .filter(|l| l.contains("Database connection attempt"))
+            .collect();
+        assert_eq!(retries.len(), 2, "logs: {}", logs);
+        assert!(retries[0].contains("attempt 1/3"));
+        assert!(retries[1].contains("attempt 2/3"));
+        let err = result.expect_err("unreachable database should fail");
+        assert!(
+            err.to_string().contains("127.0.0.1"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_retry_with_backoff_stops_on_success() {
+        let policy = RetryPolicy {
+            attempts: 5,
+            base_delay: Duration::from_millis(1),
+        };
+        let mut calls = 0;
+
+        let result: Result<u32, String> = retry_with_backoff(&policy, || {
+            calls += 1;
+            if calls < 2 {
+                Err("not ready".to_string())
+            } else {
+                Ok(calls)
+            }
+        });
+
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn test_group_into_cycles_orders_oldest_first() {
+        let id_to_name: HashMap<i32, String> = [(1, "Alice"), (2, "Bob"), (3, "Charlie")]
+            .into_iter()
+            .map(|(i, n)| (i, n.to_string()))
+            .collect();
+
+        // Rows out of order, with the first cycle split across non-adjacent rows
+        let rows = vec![
+            row(5, 1, "Tank", 29),
+            row(1, 1, "Parlor", 1),
+            row(4, 2, "Bin", 15),
+            row(2, 2, "Parlor", 1),
+            row(3, 3, "Bin", 1),
+        ];
+
+        let cycles = group_into_cycles(&rows, &id_to_name);
+
+        assert_eq!(cycles.len(), 3);
+        assert_eq!(cycles[0].assigned_at, at(1));
+        assert_eq!(cycles[1].assigned_at, at(15));
+        assert_eq!(cycles[2].assigned_at, at(29));
+
+        assert_eq!(cycles[0].tasks["Parlor"], vec!["Alice", "Bob"]);
+        assert_eq!(cycles[0].tasks["Bin"], vec!["Charlie"]);
+        assert_eq!(cycles[1].tasks["Bin"], vec!["Bob"]);
+        assert_eq!(cycles[2].tasks["Tank"], vec!["Alice"]);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_fetch_fairness_trend_flags_uneven_cycle() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let mut conn = PgConnection::establish(&url).expect("connect to test database");
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            let ids: Vec<i32> = ["Fair A", "Fair B", "Fair C"]
+                .into_iter()
+                .map(|name| {
+                    diesel::insert_into(people_dsl::people)
+                        .values(&NewPerson {
+                            name,
+                            group_type: "A",
+                        })
+                        .returning(people_dsl::id)
+                        .get_result(conn)
+                })
+                .collect::<QueryResult<_>>()?;
+
+            // Far in the future so these are the two most recent cycles in any database
+            let even = NaiveDate::from_ymd_opt(2999, 1, 1)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap();
+            let uneven = NaiveDate::from_ymd_opt(2999, 1, 15)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap();
+            let rows = [
+                (ids[0], even),
+                (ids[1], even),
+                (ids[2], even),
+                (ids[0], uneven),
+                (ids[0], uneven),
+                (ids[0], uneven),
+            ];
+            for (person_id, assigned_at) in rows {
                 diesel::insert_into(assignments_dsl::assignments)
-                    .values(&new_assign)
+                    .values(&NewAssignment {
+                        person_id,
+                        task_name: "Parlor",
+                        assigned_at,
+                        run_id: None,
+                    })
                     .execute(conn)?;
             }
-        }
+
+            let trend = fetch_fairness_trend(conn, 2)?;
+
+            assert_eq!(trend.len(), 2);
+            assert_eq!(trend[0].assigned_at, even);
+            assert_eq!(trend[1].assigned_at, uneven);
+            assert_eq!(trend[0].assignments, 3);
+            assert_eq!(trend[1].assignments, 3);
+            assert!(trend[1].variance > trend[0].variance);
+            Ok(())
+        });
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_fetch_people_with_last_assignment() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let mut conn = PgConnection::establish(&url).expect("connect to test database");
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            let mut insert_person = |name| {
+                diesel::insert_into(people_dsl::people)
+                    .values(&NewPerson {
+                        name,
+                        group_type: "A",
+                    })
+                    .returning(people_dsl::id)
+                    .get_result::<i32>(conn)
+            };
+            let veteran = insert_person("Stats Veteran")?;
+            insert_person("Stats Newcomer")?;
+
+            for day in [1, 15] {
+                diesel::insert_into(assignments_dsl::assignments)
+                    .values(&NewAssignment {
+                        person_id: veteran,
+                        task_name: "Parlor",
+                        assigned_at: at(day),
+                        run_id: None,
+                    })
+                    .execute(conn)?;
+            }
+
+            let people = fetch_people_with_last_assignment(conn)?;
+            let find = |name: &str| {
+                people
+                    .iter()
+                    .find(|p| p.person.name == name)
+                    .expect("person listed")
+            };
+
+            assert_eq!(find("Stats Veteran").last_assigned_at, Some(at(15)));
+            assert_eq!(find("Stats Newcomer").last_assigned_at, None);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_last_task_dates_keeps_most_recent() {
+        let id_to_name: HashMap<i32, String> = [(1, "Alice".to_string())].into_iter().collect();
+        let rows = vec![
+            row(1, 1, "Tank", 1),
+            row(2, 1, "Bin", 15),
+            row(3, 1, "Tank", 29),
+        ];
+
+        let last_done = last_task_dates(&group_into_cycles(&rows, &id_to_name));
+
+        assert_eq!(last_done["Alice"]["Tank"], at(29));
+        assert_eq!(last_done["Alice"]["Bin"], at(15));
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_history_across_saved_runs() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let mut conn = PgConnection::establish(&url).unwrap();
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            let mut name_to_id = HashMap::new();
+            for name in ["History Alice", "History Bob"] {
+                let id: i32 = diesel::insert_into(people_dsl::people)
+                    .values(&NewPerson {
+                        name,
+                        group_type: "A",
+                    })
+                    .returning(people_dsl::id)
+                    .get_result(conn)?;
+                name_to_id.insert(name.to_string(), id);
+            }
+
+            let runs = [("Parlor", "Bin"), ("Bin", "Tank"), ("Tank", "Parlor")];
+            for (alice_task, bob_task) in runs {
+                let assignments = HashMap::from([
+                    (alice_task.to_string(), vec!["History Alice".to_string()]),
+                    (bob_task.to_string(), vec!["History Bob".to_string()]),
+                ]);
+                save_assignments(conn, &assignments, &name_to_id, &RunMeta::default())?;
+                // Each run is stamped with the current time; keep the stamps distinct
+                std::thread::sleep(Duration::from_millis(5));
+            }
+
+            let cycles = fetch_history_detailed(conn, &name_to_id)?;
+            assert_eq!(cycles.len(), 3);
+            assert!(cycles
+                .windows(2)
+                .all(|w| w[0].assigned_at < w[1].assigned_at));
+            for (cycle, (alice_task, bob_task)) in cycles.iter().zip(runs) {
+                assert_eq!(cycle.tasks[alice_task], vec!["History Alice"]);
+                assert_eq!(cycle.tasks[bob_task], vec!["History Bob"]);
+            }
+
+            let history = recent_history(&cycles, 2);
+            assert_eq!(history["History Alice"], vec!["Tank", "Bin"]);
+            assert_eq!(history["History Bob"], vec!["Parlor", "Tank"]);
+            assert_eq!(fetch_history(conn, &name_to_id, 2)?, history);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_recent_history_newest_first_and_truncated() {
+        let id_to_name: HashMap<i32, String> = [(1, "Alice".to_string())].into_iter().collect();
+        let rows: Vec<Assignment> = (1..=7)
+            .map(|day| row(day as i32, 1, &format!("Task {}", day), day))
+            .collect();
+
+        let history = recent_history(&group_into_cycles(&rows, &id_to_name), 5);
+
+        assert_eq!(
+            history["Alice"],
+            vec!["Task 7", "Task 6", "Task 5", "Task 4", "Task 3"]
+        );
+    }
+
+    #[test]
+    fn test_recent_history_window_controls_how_far_back() {
+        let id_to_name: HashMap<i32, String> = [(1, "Alice".to_string())].into_iter().collect();
+        let rows = vec![
+            row(1, 1, "Tank", 1),
+            row(2, 1, "Bin", 15),
+            row(3, 1, "Parlor", 29),
+        ];
+        let cycles = group_into_cycles(&rows, &id_to_name);
+
+        let short = recent_history(&cycles, 1);
+        let long = recent_history(&cycles, 10);
+
+        assert_eq!(short["Alice"], vec!["Parlor"]);
+        assert_eq!(long["Alice"], vec!["Parlor", "Bin", "Tank"]);
+    }
+
+    #[test]
+    fn test_group_into_cycles_skips_unknown_people() {
+        let id_to_name: HashMap<i32, String> = [(1, "Alice".to_string())].into_iter().collect();
+        let rows = vec![
+            row(1, 1, "Parlor", 1),
+            row(2, 99, "Parlor", 1),
+            row(3, 99, "Bin", 15),
+        ];
+
+        let cycles = group_into_cycles(&rows, &id_to_name);
+
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].tasks.len(), 1);
+        assert_eq!(cycles[0].tasks["Parlor"], vec!["Alice"]);
     }
-    Ok(())
 }
@@ -1,17 +1,1106 @@
+use crate::people_config::{task_key, PeopleConfiguration, PersonConfig};
 use anyhow::{bail, Result};
+use chrono::NaiveDateTime;
 use rand::seq::SliceRandom;
-use std::collections::{HashMap, HashSet};
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use thiserror::Error;
 
-/// Generates new work assignments using a hybrid rotation strategy to satisfy all constraints.
+/// Reasons a requested distribution can never succeed, regardless of shuffling.
+#[derive(Error, Debug, PartialEq)]
+pub enum Infeasibility {
+    /// More spots in total than there are people
+    #[error("tasks need {required} people in total but only {available} are available")]
+    Total { required: usize, available: usize },
+
+    /// A single task has fewer eligible people than spots
+    #[error("task '{task}' needs {required} people but only {eligible} are eligible")]
+    Task {
+        task: String,
+        required: usize,
+        eligible: usize,
+    },
+
+    /// The per-person floor needs more spots than the tasks provide
+    #[error("guaranteeing {min_per_person} task(s) to each of {people} people needs {required} spots but only {capacity} exist")]
+    Floor {
+        min_per_person: usize,
+        people: usize,
+        required: usize,
+        capacity: usize,
+    },
+
+    /// The per-person floor is higher than the per-person cap
+    #[error("min_per_person ({min}) cannot exceed max_per_person ({max})")]
+    FloorAboveCap { min: usize, max: usize },
+
+    /// Tasks only a subset of groups may perform need more people than those groups have
+    #[error("tasks restricted to group(s) {groups:?} need {required} people but only {available} are available")]
+    Group {
+        groups: Vec<String>,
+        required: usize,
+        available: usize,
+    },
+
+    /// A task requires more members of a group than are eligible for it
+    #[error("task '{task}' needs at least {required} from group '{group}' but only {eligible} are eligible")]
+    GroupMinimum {
+        task: String,
+        group: String,
+        required: usize,
+        eligible: usize,
+    },
+
+    /// A task's group minimums add up to more than its spots can cover
+    #[error("group minimums for task '{task}' total {required} but its spots can cover at most {capacity}")]
+    GroupMinimumsOverCapacity {
+        task: String,
+        required: usize,
+        capacity: usize,
+    },
+
+    /// A task's minimum for a group is above its maximum for the same group
+    #[error("task '{task}' needs at least {min} from group '{group}' but allows at most {max}")]
+    GroupRange {
+        task: String,
+        group: String,
+        min: usize,
+        max: usize,
+    },
+
+    /// Group maximums leave a task with fewer usable people than spots
+    #[error(
+        "task '{task}' needs {required} people but group maximums leave only {available} usable"
+    )]
+    GroupMaximum {
+        task: String,
+        required: usize,
+        available: usize,
+    },
+
+    /// A task needs every eligible person, including both members of a conflict pair
+    #[error("task '{task}' needs every eligible person, but '{first}' and '{second}' cannot work together")]
+    Conflict {
+        task: String,
+        first: String,
+        second: String,
+    },
+}
+
+/// Pins a person to a task before the rest of the distribution is generated.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Lock {
+    pub person: String,
+    pub task: String,
+}
+
+/// Reasons a set of locks cannot be applied.
+#[derive(Error, Debug, PartialEq)]
+pub enum LockError {
+    /// Lock references a task that is not part of this distribution
+    #[error("lock for '{person}' references unknown task '{task}'")]
+    UnknownTask { person: String, task: String },
+
+    /// Lock references someone who is not an active person
+    #[error("lock references unknown or inactive person '{0}'")]
+    UnknownPerson(String),
+
+    /// The same person is locked to the same task twice
+    #[error("'{person}' is locked to task '{task}' more than once")]
+    DuplicateLock { person: String, task: String },
+
+    /// A person is locked to more tasks than the per-person cap allows
+    #[error("'{person}' is locked to more than {max_per_person} task(s)")]
+    OverPersonCap {
+        person: String,
+        max_per_person: usize,
+    },
+
+    /// Person's group is not allowed to perform the task, or they hard-avoid it
+    #[error("'{person}' is not eligible for task '{task}'")]
+    Ineligible { person: String, task: String },
+
+    /// Both members of a conflict pair are locked to the same task
+    #[error("'{first}' and '{second}' are both locked to task '{task}' but cannot work together")]
+    Conflict {
+        first: String,
+        second: String,
+        task: String,
+    },
+
+    /// More members of a group are locked to a task than its maximum allows
+    #[error("more than {max} member(s) of group '{group}' are locked to task '{task}'")]
+    OverGroupCap {
+        task: String,
+        group: String,
+        max: usize,
+    },
+
+    /// More people are locked to a task than it has spots
+    #[error("task '{task}' has {locked} locked people but only {capacity} spots")]
+    OverCapacity {
+        task: String,
+        locked: usize,
+        capacity: usize,
+    },
+}
+
+/// A placement in a finished distribution that breaks a constraint.
+#[derive(Error, Debug, PartialEq)]
+pub enum Violation {
+    /// Assignee is not an active person in the people configuration
+    #[error("task '{task}' is assigned to unknown or inactive person '{person}'")]
+    UnknownPerson { task: String, person: String },
+
+    /// Assignee appears on the same task more than once
+    #[error("'{person}' is assigned to task '{task}' more than once")]
+    DuplicateAssignee { task: String, person: String },
+
+    /// Assignee has the task in their `hard_avoid_tasks`
+    #[error("'{person}' must never be assigned task '{task}'")]
+    HardAvoided { task: String, person: String },
+
+    /// One of the assignee's groups is not allowed to perform the task
+    #[error("'{person}' ({groups}) is not allowed to perform task '{task}'")]
+    Forbidden {
+        task: String,
+        person: String,
+        groups: String,
+    },
+
+    /// Both members of a conflict pair share the task
+    #[error("'{first}' and '{second}' share task '{task}' but cannot work together")]
+    Conflict {
+        task: String,
+        first: String,
+        second: String,
+    },
+
+    /// The task got fewer members of a group than its minimum
+    #[error("task '{task}' has {assigned} member(s) of group '{group}' but needs at least {min}")]
+    BelowGroupMinimum {
+        task: String,
+        group: String,
+        min: usize,
+        assigned: usize,
+    },
+
+    /// The task got more members of a group than its maximum
+    #[error("task '{task}' has {assigned} member(s) of group '{group}' but allows at most {max}")]
+    AboveGroupMaximum {
+        task: String,
+        group: String,
+        max: usize,
+        assigned: usize,
+    },
+
+    /// A person was given more tasks than the per-person cap allows
+    #[error("'{person}' has {assigned} tasks but may have at most {max_per_person}")]
+    OverPersonCap {
+        person: String,
+        max_per_person: usize,
+        assigned: usize,
+    },
+}
+
+/// How one task's assignees changed between two cycles. Names are sorted.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TaskDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+/// Per-task changes between two cycles, covering tasks present in either one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AssignmentDiff {
+    pub tasks: BTreeMap<String, TaskDiff>,
+}
+
+impl AssignmentDiff {
+    /// True if anyone was added to or removed from any task
+    pub fn has_changes(&self) -> bool {
+        self.tasks
+            .values()
+            .any(|t| !t.added.is_empty() || !t.removed.is_empty())
+    }
+}
+
+/// Compares two task -> people maps, reporting who joined, left, or stayed on each task.
+/// Pass an empty `previous` for the first-ever run; everyone is then reported as added.
+pub fn diff_assignments(
+    previous: &HashMap<String, Vec<String>>,
+    current: &HashMap<String, Vec<String>>,
+) -> AssignmentDiff {
+    let empty = Vec::new();
+    let all_tasks: BTreeSet<&String> = previous.keys().chain(current.keys()).collect();
+
+    let tasks = all_tasks
+        .into_iter()
+        .map(|task| {
+            let before: BTreeSet<&String> = previous.get(task).unwrap_or(&empty).iter().collect();
+            let after: BTreeSet<&String> = current.get(task).unwrap_or(&empty).iter().collect();
+
+            let diff = TaskDiff {
+                added: after.difference(&before).map(|n| n.to_string()).collect(),
+                removed: before.difference(&after).map(|n| n.to_string()).collect(),
+                unchanged: after.intersection(&before).map(|n| n.to_string()).collect(),
+            };
+            (task.clone(), diff)
+        })
+        .collect();
+
+    AssignmentDiff { tasks }
+}
+
+/// Optional rules shaping a single distribution run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistributionRules {
+    /// People pinned to specific tasks
+    pub locks: Vec<Lock>,
+    /// Most tasks any one person may receive in a cycle
+    pub max_per_person: usize,
+    /// Tasks every person is guaranteed before the remaining spots are filled freely
+    pub min_per_person: usize,
+    /// Calendar-based minimum gap before someone repeats the same task
+    pub same_task_gap: Option<SameTaskGap>,
+    /// Pairs of people who must never share a task
+    pub conflicts: Vec<[String; 2]>,
+    /// Person -> tasks they must never be given, whatever else allows it
+    pub hard_avoid: HashMap<String, Vec<String>>,
+    /// Person -> task -> soft constraint weight. Penalised people are less likely to be
+    /// picked for the task, but still are when nobody else can take it.
+    pub soft_penalties: HashMap<String, HashMap<String, u32>>,
+    /// Task -> group -> fewest members of that group the task must get.
+    /// Entries for tasks outside the distribution are ignored.
+    pub min_from_group: HashMap<String, HashMap<String, usize>>,
+    /// Task -> group -> most members of that group the task may get.
+    /// Entries for tasks outside the distribution are ignored.
+    pub max_from_group: HashMap<String, HashMap<String, usize>>,
+}
+
+/// Keeps people off a task they did fewer than `min_days` days ago.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SameTaskGap {
+    pub min_days: i64,
+    /// Person -> task -> when they last did it
+    pub last_done: HashMap<String, HashMap<String, NaiveDateTime>>,
+    /// Reference time the gap is measured from
+    pub now: NaiveDateTime,
+    /// If the gap leaves a task with fewer candidates than spots, let back in
+    /// whoever did it longest ago instead of failing
+    pub relax_oldest_first: bool,
+}
+
+impl SameTaskGap {
+    /// When the person last did the task, if that was inside the window.
+    /// The gap counts calendar days, so the time of day on either end does not matter.
+    fn blocked_since(&self, person: &str, task: &str) -> Option<NaiveDateTime> {
+        self.last_done
+            .get(person)
+            .and_then(|tasks| tasks.get(task))
+            .copied()
+            .filter(|&last| (self.now.date() - last.date()).num_days() < self.min_days)
+    }
+}
+
+impl Default for DistributionRules {
+    fn default() -> Self {
+        Self {
+            locks: Vec::new(),
+            max_per_person: 1,
+            min_per_person: 0,
+            same_task_gap: None,
+            conflicts: Vec::new(),
+            hard_avoid: HashMap::new(),
+            soft_penalties: HashMap::new(),
+            min_from_group: HashMap::new(),
+            max_from_group: HashMap::new(),
+        }
+    }
+}
+
+impl DistributionRules {
+    /// Relative chance of picking a person for a task: 1 unpenalised, `1 / (1 + weight)` otherwise
+    fn pick_weight(&self, person: &str, task: &str) -> f64 {
+        let penalty = self
+            .soft_penalties
+            .get(person)
+            .and_then(|tasks| tasks.get(task))
+            .copied()
+            .unwrap_or(0);
+        1.0 / (1.0 + f64::from(penalty))
+    }
+
+    /// True if the person listed the task in their hard avoids, ignoring case
+    fn hard_avoids(&self, person: &str, task: &str) -> bool {
+        let key = task_key(task);
+        self.hard_avoid
+            .get(person)
+            .is_some_and(|tasks| tasks.iter().any(|t| task_key(t) == key))
+    }
+
+    /// (task, group, minimum) for every group minimum on a task in `work_areas`, sorted
+    fn group_minimums<'a>(
+        &'a self,
+        work_areas: &HashMap<String, usize>,
+    ) -> Vec<(&'a String, &'a String, usize)> {
+        let mut minimums: Vec<_> = self
+            .min_from_group
+            .iter()
+            .filter(|(task, _)| work_areas.contains_key(*task))
+            .flat_map(|(task, groups)| groups.iter().map(move |(g, &min)| (task, g, min)))
+            .filter(|&(_, _, min)| min > 0)
+            .collect();
+        minimums.sort();
+        minimums
+    }
+
+    /// (task, group, maximum) for every group maximum on a task in `work_areas`, sorted
+    fn group_maximums<'a>(
+        &'a self,
+        work_areas: &HashMap<String, usize>,
+    ) -> Vec<(&'a String, &'a String, usize)> {
+        let mut maximums: Vec<_> = self
+            .max_from_group
+            .iter()
+            .filter(|(task, _)| work_areas.contains_key(*task))
+            .flat_map(|(task, groups)| groups.iter().map(move |(g, &max)| (task, g, max)))
+            .collect();
+        maximums.sort();
+        maximums
+    }
+
+    /// Person -> everyone they must not share a task with
+    fn conflict_partners(&self) -> HashMap<&str, Vec<&str>> {
+        let mut partners: HashMap<&str, Vec<&str>> = HashMap::new();
+        for [first, second] in &self.conflicts {
+            partners.entry(first).or_default().push(second);
+            partners.entry(second).or_default().push(first);
+        }
+        partners
+    }
+}
+
+/// Members of each group, keyed by group identifier. A person may appear under several groups.
+pub type GroupMembers = HashMap<String, Vec<String>>;
+
+/// Builds the group map for the legacy two-group (A/B) setup.
+pub fn two_groups(names_a: &[String], names_b: &[String]) -> GroupMembers {
+    HashMap::from([
+        ("A".to_string(), names_a.to_vec()),
+        ("B".to_string(), names_b.to_vec()),
+    ])
+}
+
+/// Tasks each group is not allowed to perform, keyed by group identifier.
+pub fn group_constraints() -> HashMap<String, Vec<String>> {
+    HashMap::from([
+        ("A".to_string(), vec!["Toilet B".to_string()]),
+        ("B".to_string(), vec!["Toilet A".to_string()]),
+    ])
+}
+
+/// The built-in `group_constraints`, minus any exclusion the group also declares in the people
+/// configuration. Declared constraints are enforced at their configured severity, so a soft
+/// weight on `cannot_perform_toilet_b` softens group A's built-in Toilet B rule.
+pub fn built_in_constraints(people_config: &PeopleConfiguration) -> HashMap<String, Vec<String>> {
+    let mut constraints = group_constraints();
+    for (group, tasks) in constraints.iter_mut() {
+        if let Some(config) = people_config.groups.get(group) {
+            tasks.retain(|task| !config.constrains_task(task));
+        }
+    }
+    constraints
+}
+
+/// The built-in `group_constraints` merged with every group's hard `cannot_perform_<task>`
+/// constraints from the people configuration, so new groups need no code changes.
+pub fn configured_constraints(
+    people_config: &PeopleConfiguration,
+    work_areas: &HashMap<String, usize>,
+) -> HashMap<String, Vec<String>> {
+    let mut constraints = built_in_constraints(people_config);
+    for (group, tasks) in people_config.task_exclusions(work_areas.keys()) {
+        let excluded = constraints.entry(group).or_default();
+        for task in tasks {
+            if !excluded.contains(&task) {
+                excluded.push(task);
+            }
+        }
+    }
+    constraints
+}
+
+/// Person -> groups they belong to
+fn memberships(groups: &GroupMembers) -> HashMap<&String, Vec<&String>> {
+    let mut memberships: HashMap<&String, Vec<&String>> = HashMap::new();
+    for (group, names) in groups {
+        for name in names {
+            memberships.entry(name).or_default().push(group);
+        }
+    }
+    memberships
+}
+
+/// True if any of the given groups is excluded from the task
+fn is_excluded_for_any(
+    constraints: &HashMap<String, Vec<String>>,
+    groups: &[&String],
+    area: &str,
+) -> bool {
+    groups.iter().any(|g| is_excluded(constraints, g, area))
+}
+
+fn is_excluded(constraints: &HashMap<String, Vec<String>>, group: &str, area: &str) -> bool {
+    constraints
+        .get(group)
+        .is_some_and(|tasks| tasks.iter().any(|t| t == area))
+}
+
+/// True if one of the person's groups may not perform the task, either by the built-in
+/// `constraints` (see `built_in_constraints`) or by a hard `cannot_perform_<task>` constraint in
+/// the people configuration. `verify_assignment` and the grouped output both use this check.
+pub fn is_forbidden(
+    constraints: &HashMap<String, Vec<String>>,
+    people_config: &PeopleConfiguration,
+    person: &PersonConfig,
+    task: &str,
+) -> bool {
+    person
+        .groups
+        .iter()
+        .any(|g| is_excluded(constraints, g, task))
+        || people_config.forbids_task(person, task)
+}
+
+/// Re-checks a finished distribution independently of `distribute_work`.
+///
+/// Every assignee must be an active person, appear at most once per task, not hard-avoid the
+/// task, and not belong to a group excluded from the task, either by `group_constraints` or by a
+/// hard `cannot_perform_<task>` constraint in the people configuration. Each task must also keep
+/// conflict pairs apart and respect its per-group minimums and maximums, and nobody may exceed
+/// `max_per_person`. All violations are collected in task order, followed by per-person ones.
+pub fn verify_assignment(
+    assignments: &HashMap<String, Vec<String>>,
+    people_config: &PeopleConfiguration,
+    rules: &DistributionRules,
+) -> std::result::Result<(), Vec<Violation>> {
+    let constraints = built_in_constraints(people_config);
+    let mut tasks: Vec<&String> = assignments.keys().collect();
+    tasks.sort();
+
+    let mut violations = Vec::new();
+    let mut tasks_per_person: BTreeMap<&String, usize> = BTreeMap::new();
+    for task in tasks {
+        let mut seen = HashSet::new();
+        let mut group_counts: HashMap<&String, usize> = HashMap::new();
+        for name in &assignments[task] {
+            if !seen.insert(name) {
+                violations.push(Violation::DuplicateAssignee {
+                    task: task.clone(),
+                    person: name.clone(),
+                });
+                continue;
+            }
+
+            let person = match people_config.find_person(name) {
+                Some(p) if p.active => p,
+                _ => {
+                    violations.push(Violation::UnknownPerson {
+                        task: task.clone(),
+                        person: name.clone(),
+                    });
+                    continue;
+                }
+            };
+            *tasks_per_person.entry(name).or_default() += 1;
+            for group in &person.groups {
+                *group_counts.entry(group).or_default() += 1;
+            }
+
+            if person.avoids_task(task) {
+                violations.push(Violation::HardAvoided {
+                    task: task.clone(),
+                    person: name.clone(),
+                });
+            }
+
+            if is_forbidden(&constraints, people_config, person, task) {
+                violations.push(Violation::Forbidden {
+                    task: task.clone(),
+                    person: name.clone(),
+                    groups: person.groups.join("/"),
+                });
+            }
+        }
+
+        for [first, second] in &rules.conflicts {
+            if seen.contains(first) && seen.contains(second) {
+                violations.push(Violation::Conflict {
+                    task: task.clone(),
+                    first: first.clone(),
+                    second: second.clone(),
+                });
+            }
+        }
+
+        let assigned = |group: &String| group_counts.get(group).copied().unwrap_or(0);
+        let mut minimums: Vec<(&String, usize)> = rules
+            .min_from_group
+            .get(task)
+            .into_iter()
+            .flatten()
+            .map(|(group, &min)| (group, min))
+            .collect();
+        minimums.sort();
+        for (group, min) in minimums {
+            if assigned(group) < min {
+                violations.push(Violation::BelowGroupMinimum {
+                    task: task.clone(),
+                    group: group.clone(),
+                    min,
+                    assigned: assigned(group),
+                });
+            }
+        }
+
+        let mut maximums: Vec<(&String, usize)> = rules
+            .max_from_group
+            .get(task)
+            .into_iter()
+            .flatten()
+            .map(|(group, &max)| (group, max))
+            .collect();
+        maximums.sort();
+        for (group, max) in maximums {
+            if assigned(group) > max {
+                violations.push(Violation::AboveGroupMaximum {
+                    task: task.clone(),
+                    group: group.clone(),
+                    max,
+                    assigned: assigned(group),
+                });
+            }
+        }
+    }
+
+    for (person, assigned) in tasks_per_person {
+        if assigned > rules.max_per_person {
+            violations.push(Violation::OverPersonCap {
+                person: person.clone(),
+                max_per_person: rules.max_per_person,
+                assigned,
+            });
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Checks whether the requested spots can be filled at all given group exclusions.
+///
+/// Each person can take at most `max_per_person` tasks and never the same task twice, so this
+/// verifies total demand, per-task eligible supply, and that tasks reserved for a subset of groups
+/// fit within those groups. It also checks the `min_per_person` floor fits in the available spots.
+/// A person listed in several groups is counted once and bound by all of those groups' exclusions.
+pub fn check_feasibility(
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    constraints: &HashMap<String, Vec<String>>,
+    rules: &DistributionRules,
+) -> Result<(), Infeasibility> {
+    let max_per_person = rules.max_per_person;
+    if rules.min_per_person > max_per_person {
+        return Err(Infeasibility::FloorAboveCap {
+            min: rules.min_per_person,
+            max: max_per_person,
+        });
+    }
+
+    // Bucket people by the exact set of groups they belong to
+    let members = memberships(groups);
+    let mut type_counts: HashMap<BTreeSet<&str>, usize> = HashMap::new();
+    for person_groups in members.values() {
+        let key = person_groups.iter().map(|g| g.as_str()).collect();
+        *type_counts.entry(key).or_default() += 1;
+    }
+    let mut types: Vec<(BTreeSet<&str>, usize)> = type_counts.into_iter().collect();
+    types.sort();
+
+    let total_required: usize = work_areas.values().sum();
+    let people: usize = types.iter().map(|(_, count)| count).sum();
+    let total_available = people * max_per_person;
+    if total_required > total_available {
+        return Err(Infeasibility::Total {
+            required: total_required,
+            available: total_available,
+        });
+    }
+
+    let floor_required = people * rules.min_per_person;
+    if floor_required > total_required {
+        return Err(Infeasibility::Floor {
+            min_per_person: rules.min_per_person,
+            people,
+            required: floor_required,
+            capacity: total_required,
+        });
+    }
+
+    let mut sorted_areas: Vec<_> = work_areas.iter().collect();
+    sorted_areas.sort();
+
+    // Which membership types may perform each task
+    let eligible_types: Vec<(&String, usize, Vec<usize>)> = sorted_areas
+        .iter()
+        .map(|(area, &required)| {
+            let allowed = (0..types.len())
+                .filter(|&i| !types[i].0.iter().any(|g| is_excluded(constraints, g, area)))
+                .collect();
+            (*area, required, allowed)
+        })
+        .collect();
+
+    for (area, required, allowed) in &eligible_types {
+        let eligible: usize = allowed.iter().map(|&i| types[i].1).sum();
+        if *required > eligible {
+            return Err(Infeasibility::Task {
+                task: (*area).clone(),
+                required: *required,
+                eligible,
+            });
+        }
+    }
+
+    // Hard avoids are individual, so recount each task person by person
+    if !rules.hard_avoid.is_empty() {
+        for (area, &required) in &sorted_areas {
+            let eligible = members
+                .iter()
+                .filter(|(person, person_groups)| {
+                    !is_excluded_for_any(constraints, person_groups, area)
+                        && !rules.hard_avoids(person, area)
+                })
+                .count();
+            if required > eligible {
+                return Err(Infeasibility::Task {
+                    task: (*area).clone(),
+                    required,
+                    eligible,
+                });
+            }
+        }
+    }
+
+    // Each group minimum must be coverable by members the task accepts; a minimum above the
+    // task's spots is reported below as GroupMinimumsOverCapacity
+    for (task, group, required) in rules.group_minimums(work_areas) {
+        let eligible = groups.get(group).map_or(0, |names| {
+            names
+                .iter()
+                .filter(|name| {
+                    !is_excluded_for_any(constraints, &members[name], task)
+                        && !rules.hard_avoids(name, task)
+                })
+                .count()
+        });
+        if required > eligible {
+            return Err(Infeasibility::GroupMinimum {
+                task: task.clone(),
+                group: group.clone(),
+                required,
+                eligible,
+            });
+        }
+    }
+
+    // Together a task's minimums must fit in its spots; a multi-group person counts toward each
+    let mut minimums_by_task: BTreeMap<&String, Vec<&String>> = BTreeMap::new();
+    let mut minimum_totals: HashMap<&String, usize> = HashMap::new();
+    for (task, group, min) in rules.group_minimums(work_areas) {
+        minimums_by_task.entry(task).or_default().push(group);
+        *minimum_totals.entry(task).or_default() += min;
+    }
+    for (task, min_groups) in &minimums_by_task {
+        let mut coverage: Vec<usize> = members
+            .iter()
+            .filter(|(name, person_groups)| {
+                !is_excluded_for_any(constraints, person_groups, task)
+                    && !rules.hard_avoids(name, task)
+            })
+            .map(|(_, person_groups)| {
+                min_groups
+                    .iter()
+                    .filter(|g| person_groups.contains(g))
+                    .count()
+            })
+            .collect();
+        coverage.sort_unstable_by(|a, b| b.cmp(a));
+        let capacity: usize = coverage.iter().take(work_areas[*task]).sum();
+        let required = minimum_totals[task];
+        if required > capacity {
+            return Err(Infeasibility::GroupMinimumsOverCapacity {
+                task: (*task).clone(),
+                required,
+                capacity,
+            });
+        }
+    }
+
+    // Each group maximum must leave room for the minimum, and the task must still fill
+    let mut maximums_by_task: BTreeMap<&String, Vec<(&String, usize)>> = BTreeMap::new();
+    for (task, group, max) in rules.group_maximums(work_areas) {
+        let min = rules
+            .min_from_group
+            .get(task)
+            .and_then(|mins| mins.get(group))
+            .copied()
+            .unwrap_or(0);
+        if min > max {
+            return Err(Infeasibility::GroupRange {
+                task: task.clone(),
+                group: group.clone(),
+                min,
+                max,
+            });
+        }
+        maximums_by_task.entry(task).or_default().push((group, max));
+    }
+    for (task, caps) in &maximums_by_task {
+        let eligible: Vec<(&String, &Vec<&String>)> = members
+            .iter()
+            .filter(|(name, person_groups)| {
+                !is_excluded_for_any(constraints, person_groups, task)
+                    && !rules.hard_avoids(name, task)
+            })
+            .map(|(name, person_groups)| (*name, person_groups))
+            .collect();
+        let uncapped = eligible
+            .iter()
+            .filter(|(_, person_groups)| !caps.iter().any(|(g, _)| person_groups.contains(g)))
+            .count();
+        let capped: usize = caps
+            .iter()
+            .map(|(group, max)| {
+                let in_group = eligible
+                    .iter()
+                    .filter(|(_, person_groups)| person_groups.contains(group))
+                    .count();
+                in_group.min(*max)
+            })
+            .sum();
+        let available = uncapped + capped;
+        if work_areas[*task] > available {
+            return Err(Infeasibility::GroupMaximum {
+                task: (*task).clone(),
+                required: work_areas[*task],
+                available,
+            });
+        }
+    }
+
+    // A task that needs everyone eligible cannot keep a conflict pair apart
+    for [first, second] in &rules.conflicts {
+        let (Some(first_groups), Some(second_groups)) = (members.get(first), members.get(second))
+        else {
+            continue;
+        };
+        for (area, required, allowed) in &eligible_types {
+            let eligible: usize = allowed.iter().map(|&i| types[i].1).sum();
+            if *required == eligible
+                && !is_excluded_for_any(constraints, first_groups, area)
+                && !is_excluded_for_any(constraints, second_groups, area)
+            {
+                return Err(Infeasibility::Conflict {
+                    task: (*area).clone(),
+                    first: first.clone(),
+                    second: second.clone(),
+                });
+            }
+        }
+    }
+
+    // Tasks whose eligible people all fall within a subset must fit in that subset (Hall's condition)
+    let required: Vec<usize> = eligible_types.iter().map(|(_, r, _)| *r).collect();
+    let allowed: Vec<&[usize]> = eligible_types
+        .iter()
+        .map(|(_, _, a)| a.as_slice())
+        .collect();
+    let capacity: Vec<usize> = types.iter().map(|(_, n)| n * max_per_person).collect();
+    if let Some(overloaded) = overloaded_tasks(&required, &allowed, &capacity) {
+        let subset: BTreeSet<usize> = overloaded
+            .iter()
+            .flat_map(|&t| allowed[t].iter().copied())
+            .collect();
+        let required: usize = eligible_types
+            .iter()
+            .filter(|(_, _, allowed)| allowed.iter().all(|i| subset.contains(i)))
+            .map(|(_, required, _)| required)
+            .sum();
+        let available: usize = subset.iter().map(|&i| capacity[i]).sum();
+        let groups: BTreeSet<String> = subset
+            .iter()
+            .flat_map(|&i| types[i].0.iter().map(|g| g.to_string()))
+            .collect();
+        return Err(Infeasibility::Group {
+            groups: groups.into_iter().collect(),
+            required,
+            available,
+        });
+    }
+
+    Ok(())
+}
+
+/// Finds tasks that together need more spots than the people eligible for them can supply.
+///
+/// Runs a max flow from tasks (`required` spots each) to membership types (`capacity` spots each)
+/// along the `allowed` edges. If demand is left over, the tasks still reachable from an unfilled
+/// task form a set whose demand exceeds what its eligible types can give. This is Hall's condition
+/// without enumerating every subset of types, which grows exponentially with the number of
+/// distinct group combinations.
+fn overloaded_tasks(
+    required: &[usize],
+    allowed: &[&[usize]],
+    capacity: &[usize],
+) -> Option<Vec<usize>> {
+    let mut task_flow = vec![0; required.len()];
+    let mut type_flow = vec![0; capacity.len()];
+    // Task -> type -> spots routed along that edge
+    let mut edge_flow: Vec<HashMap<usize, usize>> = vec![HashMap::new(); required.len()];
+
+    loop {
+        // Breadth-first search for an augmenting path, alternating task -> type -> task
+        let mut task_parent: Vec<Option<Option<usize>>> = vec![None; required.len()];
+        let mut type_parent: Vec<Option<usize>> = vec![None; capacity.len()];
+        let mut queue: VecDeque<usize> = (0..required.len())
+            .filter(|&t| task_flow[t] < required[t])
+            .collect();
+        for &t in &queue {
+            task_parent[t] = Some(None);
+        }
+
+        let mut end = None;
+        while let Some(t) = queue.pop_front() {
+            for &p in allowed[t] {
+                if type_parent[p].is_some() {
+                    continue;
+                }
+                type_parent[p] = Some(t);
+                if type_flow[p] < capacity[p] {
+                    end = Some(p);
+                    break;
+                }
+                for (next, flows) in edge_flow.iter().enumerate() {
+                    if task_parent[next].is_none() && flows.get(&p).is_some_and(|&f| f > 0) {
+                        task_parent[next] = Some(Some(p));
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if end.is_some() {
+                break;
+            }
+        }
+
+        let Some(end) = end else {
+            let reachable: Vec<usize> = (0..required.len())
+                .filter(|&t| task_parent[t].is_some())
+                .collect();
+            return (!reachable.is_empty()).then_some(reachable);
+        };
+
+        // Walk the path back to its starting task to find how much can be pushed along it
+        let mut amount = capacity[end] - type_flow[end];
+        let mut p = end;
+        loop {
+            let t = type_parent[p].unwrap();
+            match task_parent[t].unwrap() {
+                Some(prev) => {
+                    amount = amount.min(edge_flow[t][&prev]);
+                    p = prev;
+                }
+                None => {
+                    amount = amount.min(required[t] - task_flow[t]);
+                    break;
+                }
+            }
+        }
+
+        type_flow[end] += amount;
+        let mut p = end;
+        loop {
+            let t = type_parent[p].unwrap();
+            *edge_flow[t].entry(p).or_default() += amount;
+            match task_parent[t].unwrap() {
+                Some(prev) => {
+                    *edge_flow[t].get_mut(&prev).unwrap() -= amount;
+                    p = prev;
+                }
+                None => {
+                    task_flow[t] += amount;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Checks that every lock targets a known task, an active person, and a task that
+/// person's groups may perform, without overfilling any task or exceeding the per-person cap.
+pub fn validate_locks(
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    constraints: &HashMap<String, Vec<String>>,
+    rules: &DistributionRules,
+) -> Result<(), LockError> {
+    let memberships = memberships(groups);
+    let mut seen_locks = HashSet::new();
+    let mut locked_per_person: HashMap<&str, usize> = HashMap::new();
+    let mut locked_per_task: HashMap<&str, usize> = HashMap::new();
+
+    for lock in &rules.locks {
+        let Some(&capacity) = work_areas.get(&lock.task) else {
+            return Err(LockError::UnknownTask {
+                person: lock.person.clone(),
+                task: lock.task.clone(),
+            });
+        };
+
+        let Some(person_groups) = memberships.get(&lock.person) else {
+            return Err(LockError::UnknownPerson(lock.person.clone()));
+        };
+
+        if !seen_locks.insert((&lock.person, &lock.task)) {
+            return Err(LockError::DuplicateLock {
+                person: lock.person.clone(),
+                task: lock.task.clone(),
+            });
+        }
+
+        let person_locks = locked_per_person.entry(&lock.person).or_default();
+        *person_locks += 1;
+        if *person_locks > rules.max_per_person {
+            return Err(LockError::OverPersonCap {
+                person: lock.person.clone(),
+                max_per_person: rules.max_per_person,
+            });
+        }
+
+        if is_excluded_for_any(constraints, person_groups, &lock.task)
+            || rules.hard_avoids(&lock.person, &lock.task)
+        {
+            return Err(LockError::Ineligible {
+                person: lock.person.clone(),
+                task: lock.task.clone(),
+            });
+        }
+
+        if let Some(other) = rules.locks.iter().find(|other| {
+            other.task == lock.task
+                && other.person != lock.person
+                && rules
+                    .conflicts
+                    .iter()
+                    .any(|pair| pair.contains(&lock.person) && pair.contains(&other.person))
+        }) {
+            return Err(LockError::Conflict {
+                first: lock.person.clone(),
+                second: other.person.clone(),
+                task: lock.task.clone(),
+            });
+        }
+
+        let locked = locked_per_task.entry(&lock.task).or_default();
+        *locked += 1;
+        if *locked > capacity {
+            return Err(LockError::OverCapacity {
+                task: lock.task.clone(),
+                locked: *locked,
+                capacity,
+            });
+        }
+    }
+
+    for (task, group, max) in rules.group_maximums(work_areas) {
+        let locked = rules
+            .locks
+            .iter()
+            .filter(|lock| {
+                &lock.task == task && groups.get(group).is_some_and(|m| m.contains(&lock.person))
+            })
+            .count();
+        if locked > max {
+            return Err(LockError::OverGroupCap {
+                task: task.clone(),
+                group: group.clone(),
+                max,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Two-group form of `distribute_by_group`, using the built-in `group_constraints`.
 pub fn distribute_work(
     names_a: &[String],
     names_b: &[String],
     work_areas: &HashMap<String, usize>,
     history: &HashMap<String, Vec<String>>,
+    rules: &DistributionRules,
+) -> Result<HashMap<String, Vec<String>>> {
+    distribute_by_group(
+        &two_groups(names_a, names_b),
+        work_areas,
+        &group_constraints(),
+        history,
+        rules,
+    )
+}
+
+/// Generates new work assignments using a hybrid rotation strategy to satisfy all constraints.
+///
+/// Locked people are placed on their task first and the remaining spots are filled around them.
+/// Once someone reaches `max_per_person` tasks they are removed from every remaining pool.
+/// Anyone in a group that `constraints` excludes from a task is never placed on it.
+pub fn distribute_by_group(
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    constraints: &HashMap<String, Vec<String>>,
+    history: &HashMap<String, Vec<String>>,
+    rules: &DistributionRules,
+) -> Result<HashMap<String, Vec<String>>> {
+    distribute_by_group_with_rng(
+        groups,
+        work_areas,
+        constraints,
+        history,
+        rules,
+        &mut rand::thread_rng(),
+    )
+}
+
+/// `distribute_by_group` drawing from the given RNG. Ties are broken by name rather than by hash
+/// order, so the same seed and inputs always produce the same distribution.
+pub fn distribute_by_group_with_rng<R: Rng + ?Sized>(
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    constraints: &HashMap<String, Vec<String>>,
+    history: &HashMap<String, Vec<String>>,
+    rules: &DistributionRules,
+    rng: &mut R,
 ) -> Result<HashMap<String, Vec<String>>> {
-    let all_people: HashSet<String> = names_a.iter().chain(names_b.iter()).cloned().collect();
-    let names_a_set: HashSet<_> = names_a.iter().cloned().collect();
-    let names_b_set: HashSet<_> = names_b.iter().cloned().collect();
+    validate_locks(groups, work_areas, constraints, rules)?;
+
+    let memberships = memberships(groups);
+    let all_people: HashSet<String> = memberships.keys().map(|p| p.to_string()).collect();
 
     let mut assignments: HashMap<String, Vec<String>> = HashMap::new();
     for area in work_areas.keys() {
@@ -20,75 +1109,319 @@ pub fn distribute_work(
 
     // Step 1: Pre-calculate all possible candidates for every task
     let mut candidates: HashMap<String, HashSet<String>> = HashMap::new();
-    for (area, _) in work_areas {
+    for area in work_areas.keys() {
         let mut area_candidates = HashSet::new();
+        let mut gap_blocked: Vec<(NaiveDateTime, &String)> = Vec::new();
         for person in &all_people {
             let person_history = history.get(person).map_or(Vec::new(), |h| h.clone());
 
             // --- HYBRID ELIGIBILITY CHECK ---
             let has_worked_here_recently = if *area == "Toilet B" {
                 // For the highly constrained Toilet B, only check the single most recent assignment.
-                person_history
-                    .first()
-                    .map_or(false, |last_area| last_area == area)
+                person_history.first() == Some(area)
             } else {
                 // For all other tasks, use the standard long-term history check.
                 person_history.contains(area)
             };
 
             // Reinstate the original strict rules.
-            let is_from_b_in_toilet_a = *area == "Toilet A" && names_b_set.contains(person);
-            let is_from_a_in_toilet_b = *area == "Toilet B" && names_a_set.contains(person);
+            let is_excluded_by_group = is_excluded_for_any(constraints, &memberships[person], area);
+
+            // Hard avoids are absolute; unlike the history and gap rules they are never relaxed
+            let is_hard_avoided = rules.hard_avoids(person, area);
 
             // A person is eligible if they meet all conditions.
-            if !has_worked_here_recently && !is_from_b_in_toilet_a && !is_from_a_in_toilet_b {
+            if !has_worked_here_recently && !is_excluded_by_group && !is_hard_avoided {
+                match rules
+                    .same_task_gap
+                    .as_ref()
+                    .and_then(|gap| gap.blocked_since(person, area))
+                {
+                    Some(last_done) => gap_blocked.push((last_done, person)),
+                    None => {
+                        area_candidates.insert(person.clone());
+                    }
+                }
+            }
+        }
+
+        // Relax the gap oldest-first rather than leave the task short
+        if rules
+            .same_task_gap
+            .as_ref()
+            .is_some_and(|gap| gap.relax_oldest_first)
+        {
+            gap_blocked.sort();
+            for (_, person) in gap_blocked {
+                if area_candidates.len() >= work_areas[area] {
+                    break;
+                }
                 area_candidates.insert(person.clone());
             }
         }
         candidates.insert(area.clone(), area_candidates);
     }
 
+    let mut group_caps: HashMap<String, Vec<(HashSet<String>, usize)>> = HashMap::new();
+    for (task, group, max) in rules.group_maximums(work_areas) {
+        let members = groups.get(group).into_iter().flatten().cloned().collect();
+        group_caps
+            .entry(task.clone())
+            .or_default()
+            .push((members, max));
+    }
+
+    let mut placement = Placement {
+        work_areas,
+        max_per_person: rules.max_per_person,
+        assignments,
+        candidates,
+        tasks_per_person: HashMap::new(),
+        conflict_partners: rules.conflict_partners(),
+        conflict_blocked: HashMap::new(),
+        group_caps,
+    };
+    for area in work_areas.keys() {
+        placement.enforce_group_caps(area);
+    }
+
+    // Pre-place locked people before filling the rest
+    for lock in &rules.locks {
+        placement.place(&lock.person, &lock.task);
+    }
+
+    // Fill per-task group minimums before other placements compete for the spots
+    for (task, group, min) in rules.group_minimums(work_areas) {
+        let members: HashSet<&String> = groups.get(group).into_iter().flatten().collect();
+        loop {
+            let have = placement.assignments[task]
+                .iter()
+                .filter(|p| members.contains(p))
+                .count();
+            if have >= min {
+                break;
+            }
+
+            let mut open: Vec<&String> = placement.candidates[task]
+                .iter()
+                .filter(|p| members.contains(p))
+                .collect();
+            open.sort();
+            let Some(person) = open
+                .choose_weighted(rng, |p| rules.pick_weight(p, task))
+                .ok()
+                .filter(|_| placement.has_room(task))
+                .map(|p| p.to_string())
+            else {
+                bail!(
+                    "could not give task '{}' at least {} from group '{}': only {} could be placed.",
+                    task,
+                    min,
+                    group,
+                    have
+                );
+            };
+            placement.place(&person, task);
+        }
+    }
+
+    // Guarantee the floor next, most constrained people first
+    let mut people_order: Vec<&String> = all_people.iter().collect();
+    people_order.sort();
+    for _ in 0..rules.min_per_person {
+        people_order.shuffle(rng);
+        people_order.sort_by_key(|person| placement.open_tasks_for(person).len());
+
+        for person in &people_order {
+            if placement.task_count(person) >= rules.min_per_person {
+                continue;
+            }
+
+            let open_tasks = placement.open_tasks_for(person);
+            let Some(task) = open_tasks
+                .choose_weighted(rng, |t| rules.pick_weight(person, t))
+                .ok()
+                .map(|t| t.to_string())
+            else {
+                bail!(
+                    "could not guarantee '{}' at least {} task(s): no eligible task has spots left.",
+                    person,
+                    rules.min_per_person
+                );
+            };
+            placement.place(person, &task);
+        }
+    }
+
     // The rest of the algorithm (the constraint solver) remains the same.
     let total_spots_to_fill: usize = work_areas.values().sum();
     for _ in 0..total_spots_to_fill {
-        let most_constrained_task = candidates
+        let most_constrained_task = placement
+            .candidates
             .iter()
-            .filter(|(area, _)| assignments[area.as_str()].len() < work_areas[area.as_str()])
-            .min_by_key(|(_, potential_assignees)| potential_assignees.len());
+            .filter(|(area, _)| placement.has_room(area))
+            .min_by_key(|(area, potential_assignees)| (potential_assignees.len(), *area));
 
         if let Some((task_name, potential_assignees)) = most_constrained_task {
             if potential_assignees.is_empty() {
+                if let Some((blocked, partner)) = placement
+                    .conflict_blocked
+                    .get(task_name)
+                    .and_then(|pairs| pairs.first())
+                {
+                    bail!(
+                        "could not find a valid assignment. Task '{}' ran out of candidates because '{}' and '{}' cannot work together.",
+                        task_name, blocked, partner
+                    );
+                }
                 bail!(
                     "could not find a valid assignment. Task '{}' needs {} more person/people, but has no eligible candidates left.",
-                    task_name, work_areas[task_name] - assignments[task_name].len()
+                    task_name, work_areas[task_name] - placement.assignments[task_name].len()
                 );
             }
 
-            let assignees_vec: Vec<_> = potential_assignees.iter().collect();
-            let person_to_assign =
-                (*assignees_vec.choose(&mut rand::thread_rng()).unwrap()).clone();
-            assignments
-                .get_mut(task_name)
-                .unwrap()
-                .push(person_to_assign.clone());
-
-            for an_area in candidates.values_mut() {
-                an_area.remove(&person_to_assign);
-            }
+            let mut assignees_vec: Vec<_> = potential_assignees.iter().collect();
+            assignees_vec.sort();
+            let person_to_assign = (*assignees_vec
+                .choose_weighted(rng, |p| rules.pick_weight(p, task_name))
+                .unwrap())
+            .clone();
+            let task_name = task_name.clone();
+            placement.place(&person_to_assign, &task_name);
         } else {
             break;
         }
     }
 
-    Ok(assignments)
+    Ok(placement.assignments)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
+/// Calls `distribute_by_group` up to `max_attempts` times, returning the first success along with
+/// the attempt it came from, or `None` if every attempt failed.
+pub fn distribute_with_retries(
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    constraints: &HashMap<String, Vec<String>>,
+    history: &HashMap<String, Vec<String>>,
+    rules: &DistributionRules,
+    max_attempts: u32,
+) -> Option<(u32, HashMap<String, Vec<String>>)> {
+    distribute_with_retries_with_rng(
+        groups,
+        work_areas,
+        constraints,
+        history,
+        rules,
+        max_attempts,
+        &mut rand::thread_rng(),
+    )
+}
 
-    #[test]
+/// `distribute_with_retries` drawing every attempt from the given RNG, for reproducible runs.
+pub fn distribute_with_retries_with_rng<R: Rng + ?Sized>(
+    groups: &GroupMembers,
+    work_areas: &HashMap<String, usize>,
+    constraints: &HashMap<String, Vec<String>>,
+    history: &HashMap<String, Vec<String>>,
+    rules: &DistributionRules,
+    max_attempts: u32,
+    rng: &mut R,
+) -> Option<(u32, HashMap<String, Vec<String>>)> {
+    (1..=max_attempts).find_map(|attempt| {
+        distribute_by_group_with_rng(groups, work_areas, constraints, history, rules, rng)
+            .ok()
+            .map(|assignments| (attempt, assignments))
+    })
+}
+
+/// Working state while a distribution is being built.
+struct Placement<'a> {
+    work_areas: &'a HashMap<String, usize>,
+    max_per_person: usize,
+    assignments: HashMap<String, Vec<String>>,
+    /// Task -> people who may still be placed on it
+    candidates: HashMap<String, HashSet<String>>,
+    tasks_per_person: HashMap<String, usize>,
+    /// Person -> everyone they must not share a task with
+    conflict_partners: HashMap<&'a str, Vec<&'a str>>,
+    /// Task -> (person dropped from its pool, partner already on it)
+    conflict_blocked: HashMap<String, Vec<(String, String)>>,
+    /// Task -> (members of a capped group, most of them the task may get)
+    group_caps: HashMap<String, Vec<(HashSet<String>, usize)>>,
+}
+
+impl Placement<'_> {
+    /// Puts a person on a task, dropping them from every pool once they hit the cap
+    /// and their conflict partners from this task's pool.
+    fn place(&mut self, person: &str, task: &str) {
+        if let Some(assigned) = self.assignments.get_mut(task) {
+            assigned.push(person.to_string());
+        }
+        if let Some(pool) = self.candidates.get_mut(task) {
+            pool.remove(person);
+            for partner in self.conflict_partners.get(person).into_iter().flatten() {
+                if pool.remove(*partner) {
+                    self.conflict_blocked
+                        .entry(task.to_string())
+                        .or_default()
+                        .push((partner.to_string(), person.to_string()));
+                }
+            }
+        }
+
+        let count = self.tasks_per_person.entry(person.to_string()).or_default();
+        *count += 1;
+        if *count >= self.max_per_person {
+            for an_area in self.candidates.values_mut() {
+                an_area.remove(person);
+            }
+        }
+        self.enforce_group_caps(task);
+    }
+
+    /// Drops a capped group's members from the task's pool once the group has reached its maximum there.
+    fn enforce_group_caps(&mut self, task: &str) {
+        let Some(pool) = self.candidates.get_mut(task) else {
+            return;
+        };
+        for (members, max) in self.group_caps.get(task).into_iter().flatten() {
+            let on_task = self.assignments[task]
+                .iter()
+                .filter(|p| members.contains(*p))
+                .count();
+            if on_task >= *max {
+                pool.retain(|p| !members.contains(p));
+            }
+        }
+    }
+
+    fn has_room(&self, task: &str) -> bool {
+        self.assignments[task].len() < self.work_areas[task]
+    }
+
+    fn task_count(&self, person: &str) -> usize {
+        self.tasks_per_person.get(person).copied().unwrap_or(0)
+    }
+
+    /// Tasks with spots left that this person may still take, sorted for reproducible choice.
+    fn open_tasks_for(&self, person: &str) -> Vec<&String> {
+        let mut open: Vec<&String> = self
+            .candidates
+            .iter()
+            .filter(|(task, pool)| pool.contains(person) && self.has_room(task))
+            .map(|(task, _)| task)
+            .collect();
+        open.sort();
+        open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
     fn test_distribute_work_basic() {
         let names_a = vec!["Alice".to_string(), "Bob".to_string()];
         let names_b = vec!["Charlie".to_string(), "Dave".to_string()];
@@ -99,7 +1432,13 @@ mod tests {
 
         let history = HashMap::new(); // Empty history
 
-        let result = distribute_work(&names_a, &names_b, &work_areas, &history);
+        let result = distribute_work(
+            &names_a,
+            &names_b,
+            &work_areas,
+            &history,
+            &DistributionRules::default(),
+        );
 
         assert!(
             result.is_ok(),
@@ -122,11 +1461,1299 @@ mod tests {
 
         let history = HashMap::new();
 
-        let result = distribute_work(&names_a, &names_b, &work_areas, &history);
+        let result = distribute_work(
+            &names_a,
+            &names_b,
+            &work_areas,
+            &history,
+            &DistributionRules::default(),
+        );
 
         assert!(
             result.is_err(),
             "Distribution should fail with insufficient people"
         );
     }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn lock(person: &str, task: &str) -> Lock {
+        Lock {
+            person: person.to_string(),
+            task: task.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_distribute_work_honors_locks() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 2);
+        work_areas.insert("Toilet A".to_string(), 1);
+        work_areas.insert("Bin".to_string(), 1);
+
+        // Charlie did Parlor last time, but the lock overrides history
+        let mut history = HashMap::new();
+        history.insert("Charlie".to_string(), vec!["Parlor".to_string()]);
+
+        let rules = DistributionRules {
+            locks: vec![lock("Charlie", "Parlor")],
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            let assignments =
+                distribute_work(&names_a, &names_b, &work_areas, &history, &rules).unwrap();
+            assert!(assignments["Parlor"].contains(&"Charlie".to_string()));
+            assert_eq!(assignments["Parlor"].len(), 2);
+            assert_eq!(assignments["Toilet A"].len(), 1);
+            assert_eq!(assignments["Bin"].len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_validate_locks_rejects_ineligible_person() {
+        let names_a = names(&["Alice"]);
+        let names_b = names(&["Charlie"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 1);
+
+        let result = validate_locks(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules {
+                locks: vec![lock("Charlie", "Toilet A")],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            result,
+            Err(LockError::Ineligible {
+                person: "Charlie".to_string(),
+                task: "Toilet A".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_locks_rejects_over_capacity() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&[]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Bin".to_string(), 1);
+
+        let result = validate_locks(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules {
+                locks: vec![lock("Alice", "Bin"), lock("Bob", "Bin")],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            result,
+            Err(LockError::OverCapacity {
+                task: "Bin".to_string(),
+                locked: 2,
+                capacity: 1
+            })
+        );
+    }
+
+    fn assignment_map(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(task, people)| (task.to_string(), names(people)))
+            .collect()
+    }
+
+    #[test]
+    fn test_distribute_work_respects_max_per_person() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 3);
+        work_areas.insert("Frontyard".to_string(), 3);
+        work_areas.insert("Bin".to_string(), 1);
+
+        let rules = DistributionRules {
+            max_per_person: 2,
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            let assignments =
+                distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &rules).unwrap();
+
+            let mut counts: HashMap<&String, usize> = HashMap::new();
+            for people in assignments.values() {
+                let unique: HashSet<_> = people.iter().collect();
+                assert_eq!(unique.len(), people.len(), "No one works a task twice");
+                for person in people {
+                    *counts.entry(person).or_default() += 1;
+                }
+            }
+            assert!(
+                counts.values().all(|&c| c <= 2),
+                "Cap exceeded: {:?}",
+                counts
+            );
+            assert_eq!(counts.values().sum::<usize>(), 7);
+        }
+    }
+
+    #[test]
+    fn test_check_feasibility_cap_too_low() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 3);
+        work_areas.insert("Frontyard".to_string(), 3);
+
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules::default(),
+        );
+        assert_eq!(
+            result,
+            Err(Infeasibility::Total {
+                required: 6,
+                available: 4
+            })
+        );
+
+        let rules = DistributionRules {
+            max_per_person: 2,
+            ..Default::default()
+        };
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &rules,
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_distribute_work_min_per_person_covers_everyone() {
+        let names_a = names(&["Alice", "Bob", "Carol"]);
+        let names_b = names(&["Dave", "Eve", "Frank"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 3);
+        work_areas.insert("Frontyard".to_string(), 3);
+        work_areas.insert("Bin".to_string(), 2);
+
+        // With a cap of 2, eight spots could be covered by only four people
+        let rules = DistributionRules {
+            max_per_person: 2,
+            min_per_person: 1,
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            let assignments =
+                distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &rules).unwrap();
+            let assigned: HashSet<&String> = assignments.values().flatten().collect();
+            for person in names_a.iter().chain(names_b.iter()) {
+                assert!(assigned.contains(person), "{} was left out", person);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_feasibility_floor_exceeds_capacity() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 3);
+        work_areas.insert("Frontyard".to_string(), 3);
+
+        let rules = DistributionRules {
+            max_per_person: 2,
+            min_per_person: 2,
+            ..Default::default()
+        };
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &rules,
+        );
+        assert_eq!(
+            result,
+            Err(Infeasibility::Floor {
+                min_per_person: 2,
+                people: 4,
+                required: 8,
+                capacity: 6
+            })
+        );
+    }
+
+    fn days_ago(now: NaiveDateTime, days: i64) -> NaiveDateTime {
+        now - chrono::Duration::days(days)
+    }
+
+    fn gap_rules(min_days: i64, relax: bool, last_done: &[(&str, &str, i64)]) -> DistributionRules {
+        let now = chrono::NaiveDate::from_ymd_opt(2024, 6, 15)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let mut map: HashMap<String, HashMap<String, NaiveDateTime>> = HashMap::new();
+        for (person, task, days) in last_done {
+            map.entry(person.to_string())
+                .or_default()
+                .insert(task.to_string(), days_ago(now, *days));
+        }
+
+        DistributionRules {
+            same_task_gap: Some(SameTaskGap {
+                min_days,
+                last_done: map,
+                now,
+                relax_oldest_first: relax,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_same_task_gap_window_boundary() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&[]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Tank".to_string(), 1);
+
+        // Alice is inside the 7-day window, Bob is exactly at it
+        let rules = gap_rules(7, false, &[("Alice", "Tank", 6), ("Bob", "Tank", 7)]);
+
+        for _ in 0..20 {
+            let assignments =
+                distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &rules).unwrap();
+            assert_eq!(assignments["Tank"], names(&["Bob"]));
+        }
+    }
+
+    #[test]
+    fn test_same_task_gap_counts_calendar_days() {
+        let at = |day, hour| {
+            chrono::NaiveDate::from_ymd_opt(2024, 6, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        let gap = |last: NaiveDateTime| SameTaskGap {
+            min_days: 7,
+            last_done: HashMap::from([(
+                "Alice".to_string(),
+                HashMap::from([("Tank".to_string(), last)]),
+            )]),
+            now: at(8, 9),
+            relax_oldest_first: false,
+        };
+
+        // Under seven 24-hour periods apart, but seven calendar days
+        assert_eq!(gap(at(1, 23)).blocked_since("Alice", "Tank"), None);
+        // Six calendar days, even though it was early in the morning
+        assert_eq!(gap(at(2, 0)).blocked_since("Alice", "Tank"), Some(at(2, 0)));
+    }
+
+    #[test]
+    fn test_same_task_gap_relaxes_oldest_first() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&[]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Tank".to_string(), 1);
+
+        // Both did Tank recently; Alice longer ago
+        let last_done = [("Alice", "Tank", 5), ("Bob", "Tank", 2)];
+
+        let strict = gap_rules(7, false, &last_done);
+        assert!(
+            distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &strict).is_err()
+        );
+
+        let relaxed = gap_rules(7, true, &last_done);
+        for _ in 0..20 {
+            let assignments =
+                distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &relaxed)
+                    .unwrap();
+            assert_eq!(assignments["Tank"], names(&["Alice"]));
+        }
+    }
+
+    #[test]
+    fn test_diff_assignments() {
+        let previous = assignment_map(&[("Parlor", &["Alice", "Bob"]), ("Bin", &["Charlie"])]);
+        let current = assignment_map(&[("Parlor", &["Bob", "Dave"]), ("Tank", &["Alice"])]);
+
+        let diff = diff_assignments(&previous, &current);
+
+        assert!(diff.has_changes());
+        assert_eq!(diff.tasks.len(), 3);
+        assert_eq!(
+            diff.tasks["Parlor"],
+            TaskDiff {
+                added: names(&["Dave"]),
+                removed: names(&["Alice"]),
+                unchanged: names(&["Bob"]),
+            }
+        );
+        assert_eq!(diff.tasks["Bin"].removed, names(&["Charlie"]));
+        assert!(diff.tasks["Bin"].added.is_empty());
+        assert_eq!(diff.tasks["Tank"].added, names(&["Alice"]));
+    }
+
+    #[test]
+    fn test_diff_assignments_unchanged_and_first_run() {
+        let current = assignment_map(&[("Parlor", &["Bob", "Alice"])]);
+
+        let same = diff_assignments(&assignment_map(&[("Parlor", &["Alice", "Bob"])]), &current);
+        assert!(!same.has_changes());
+        assert_eq!(same.tasks["Parlor"].unchanged, names(&["Alice", "Bob"]));
+
+        let first_run = diff_assignments(&HashMap::new(), &current);
+        assert!(first_run.has_changes());
+        assert_eq!(first_run.tasks["Parlor"].added, names(&["Alice", "Bob"]));
+    }
+
+    #[test]
+    fn test_distribute_with_retries() {
+        let groups = two_groups(&names(&["Alice", "Bob"]), &names(&["Charlie"]));
+        let constraints = group_constraints();
+        let history = HashMap::new();
+        let rules = DistributionRules::default();
+
+        let infeasible = HashMap::from([("Task1".to_string(), 4)]);
+        assert!(
+            distribute_with_retries(&groups, &infeasible, &constraints, &history, &rules, 1)
+                .is_none()
+        );
+
+        let feasible = HashMap::from([("Task1".to_string(), 2), ("Task2".to_string(), 1)]);
+        let (attempt, assignments) =
+            distribute_with_retries(&groups, &feasible, &constraints, &history, &rules, 50)
+                .expect("feasible distribution should succeed");
+        assert!((1..=50).contains(&attempt));
+        assert_eq!(assignments["Task1"].len(), 2);
+        assert_eq!(assignments["Task2"].len(), 1);
+    }
+
+    #[test]
+    fn test_distribute_with_rng_is_reproducible() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let names_a = names(&["Alice", "Bob", "Charlie", "Dave"]);
+        let names_b = names(&["Erin", "Frank", "Grace", "Heidi"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+        work_areas.insert("Toilet B".to_string(), 2);
+        work_areas.insert("Parlor".to_string(), 3);
+
+        // Each run builds fresh hash maps, so only the seed keeps the result stable
+        let run = |seed: u64| {
+            distribute_with_retries_with_rng(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &HashMap::new(),
+                &DistributionRules::default(),
+                50,
+                &mut StdRng::seed_from_u64(seed),
+            )
+            .unwrap()
+        };
+
+        let first = run(7);
+        for _ in 0..10 {
+            assert_eq!(run(7), first);
+        }
+    }
+
+    #[test]
+    fn test_distribute_by_group_three_groups() {
+        let groups: GroupMembers = HashMap::from([
+            ("A".to_string(), names(&["Alice", "Bob"])),
+            ("B".to_string(), names(&["Charlie", "Dave"])),
+            ("C".to_string(), names(&["Erin", "Frank"])),
+        ]);
+        let mut constraints = group_constraints();
+        constraints.insert("C".to_string(), vec!["Parlor".to_string()]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+        work_areas.insert("Toilet B".to_string(), 2);
+        work_areas.insert("Parlor".to_string(), 2);
+
+        assert_eq!(
+            check_feasibility(
+                &groups,
+                &work_areas,
+                &constraints,
+                &DistributionRules::default()
+            ),
+            Ok(())
+        );
+
+        for _ in 0..20 {
+            let (_, assignments) = distribute_with_retries(
+                &groups,
+                &work_areas,
+                &constraints,
+                &HashMap::new(),
+                &DistributionRules::default(),
+                500,
+            )
+            .expect("three-group distribution should succeed");
+
+            for (group, members) in &groups {
+                for task in &constraints[group] {
+                    if let Some(assigned) = assignments.get(task) {
+                        assert!(
+                            !assigned.iter().any(|p| members.contains(p)),
+                            "group {} placed on excluded task {}: {:?}",
+                            group,
+                            task,
+                            assignments
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn conflict(first: &str, second: &str) -> [String; 2] {
+        [first.to_string(), second.to_string()]
+    }
+
+    #[test]
+    fn test_distribute_work_keeps_conflict_pair_apart() {
+        let names_a = names(&["Alice", "Bob", "Charlie"]);
+        let names_b = names(&["Dave"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 2);
+        work_areas.insert("Tank".to_string(), 2);
+
+        let rules = DistributionRules {
+            conflicts: vec![conflict("Alice", "Bob")],
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            let (_, assignments) = distribute_with_retries(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &HashMap::new(),
+                &rules,
+                500,
+            )
+            .expect("conflict pair can be kept apart");
+            for people in assignments.values() {
+                assert!(
+                    !(people.contains(&"Alice".to_string()) && people.contains(&"Bob".to_string())),
+                    "conflict pair shared a task: {:?}",
+                    assignments
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_conflict_forced_together_is_infeasible() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie"]);
+
+        // Charlie's group cannot do Toilet A, so it needs both Alice and Bob
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+
+        let rules = DistributionRules {
+            conflicts: vec![conflict("Alice", "Bob")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_feasibility(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &rules
+            ),
+            Err(Infeasibility::Conflict {
+                task: "Toilet A".to_string(),
+                first: "Alice".to_string(),
+                second: "Bob".to_string(),
+            })
+        );
+
+        let err = distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &rules)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Toilet A") && err.contains("cannot work together"));
+    }
+
+    #[test]
+    fn test_validate_locks_rejects_conflict_pair_on_same_task() {
+        let names_a = names(&["Alice", "Bob"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 2);
+
+        let result = validate_locks(
+            &two_groups(&names_a, &[]),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules {
+                locks: vec![lock("Alice", "Parlor"), lock("Bob", "Parlor")],
+                conflicts: vec![conflict("Alice", "Bob")],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            result,
+            Err(LockError::Conflict {
+                first: "Alice".to_string(),
+                second: "Bob".to_string(),
+                task: "Parlor".to_string(),
+            })
+        );
+    }
+
+    fn one_from_each(task: &str) -> HashMap<String, HashMap<String, usize>> {
+        HashMap::from([(
+            task.to_string(),
+            HashMap::from([("A".to_string(), 1), ("B".to_string(), 1)]),
+        )])
+    }
+
+    #[test]
+    fn test_distribute_work_meets_group_minimums() {
+        let names_a = names(&["Alice", "Bob", "Charlie"]);
+        let names_b = names(&["Dave", "Erin", "Frank"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 2);
+        work_areas.insert("Tank".to_string(), 2);
+
+        let rules = DistributionRules {
+            min_from_group: one_from_each("Parlor"),
+            ..Default::default()
+        };
+
+        for _ in 0..50 {
+            let assignments =
+                distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &rules).unwrap();
+            let parlor = &assignments["Parlor"];
+            assert_eq!(parlor.iter().filter(|p| names_a.contains(p)).count(), 1);
+            assert_eq!(parlor.iter().filter(|p| names_b.contains(p)).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_group_minimum_infeasible() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        // Group B may never do Toilet A, so it can't supply one
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+
+        let rules = DistributionRules {
+            min_from_group: one_from_each("Toilet A"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_feasibility(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &rules
+            ),
+            Err(Infeasibility::GroupMinimum {
+                task: "Toilet A".to_string(),
+                group: "B".to_string(),
+                required: 1,
+                eligible: 0,
+            })
+        );
+
+        let err = distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &rules)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Toilet A") && err.contains("group 'B'"));
+    }
+
+    #[test]
+    fn test_group_minimums_over_task_capacity_infeasible() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        // One spot cannot hold one from each group
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 1);
+
+        let rules = DistributionRules {
+            min_from_group: one_from_each("Parlor"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_feasibility(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &rules
+            ),
+            Err(Infeasibility::GroupMinimumsOverCapacity {
+                task: "Parlor".to_string(),
+                required: 2,
+                capacity: 1,
+            })
+        );
+
+        // Someone in both groups covers both minimums in a single spot
+        let both = two_groups(&names(&["Alice", "Bob"]), &names(&["Bob", "Charlie"]));
+        assert_eq!(
+            check_feasibility(&both, &work_areas, &group_constraints(), &rules),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_single_group_minimum_over_task_capacity_reports_capacity() {
+        let names_a = names(&["Alice", "Bob", "Charlie"]);
+
+        // Plenty of A members are eligible; the task just has too few spots
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 1);
+
+        let rules = DistributionRules {
+            min_from_group: at_most("Parlor", "A", 2),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_feasibility(
+                &two_groups(&names_a, &[]),
+                &work_areas,
+                &group_constraints(),
+                &rules
+            ),
+            Err(Infeasibility::GroupMinimumsOverCapacity {
+                task: "Parlor".to_string(),
+                required: 2,
+                capacity: 1,
+            })
+        );
+    }
+
+    fn at_most(task: &str, group: &str, max: usize) -> HashMap<String, HashMap<String, usize>> {
+        HashMap::from([(task.to_string(), HashMap::from([(group.to_string(), max)]))])
+    }
+
+    #[test]
+    fn test_distribute_work_respects_group_maximums() {
+        let names_a = names(&["Alice", "Bob", "Charlie"]);
+        let names_b = names(&["Dave", "Erin", "Frank"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 3);
+        work_areas.insert("Tank".to_string(), 3);
+
+        let rules = DistributionRules {
+            max_from_group: at_most("Parlor", "A", 1),
+            ..Default::default()
+        };
+
+        for _ in 0..50 {
+            let (_, assignments) = distribute_with_retries(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &HashMap::new(),
+                &rules,
+                500,
+            )
+            .expect("Parlor can take at most one from group A");
+            let parlor = &assignments["Parlor"];
+            assert_eq!(parlor.len(), 3);
+            assert!(parlor.iter().filter(|p| names_a.contains(p)).count() <= 1);
+        }
+    }
+
+    #[test]
+    fn test_group_maximum_infeasible() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 3);
+
+        let rules = DistributionRules {
+            max_from_group: at_most("Parlor", "A", 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_feasibility(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &rules
+            ),
+            Err(Infeasibility::GroupMaximum {
+                task: "Parlor".to_string(),
+                required: 3,
+                available: 2,
+            })
+        );
+
+        let rules = DistributionRules {
+            min_from_group: at_most("Parlor", "A", 2),
+            max_from_group: at_most("Parlor", "A", 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_feasibility(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &rules
+            ),
+            Err(Infeasibility::GroupRange {
+                task: "Parlor".to_string(),
+                group: "A".to_string(),
+                min: 2,
+                max: 1,
+            })
+        );
+    }
+
+    fn hard_avoid(person: &str, task: &str) -> HashMap<String, Vec<String>> {
+        HashMap::from([(person.to_string(), vec![task.to_string()])])
+    }
+
+    #[test]
+    fn test_distribute_work_never_assigns_hard_avoided_task() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 2);
+        work_areas.insert("Tank".to_string(), 1);
+
+        // Everyone else did the Tank recently, so Alice is its only candidate either way
+        let history = HashMap::from([
+            ("Bob".to_string(), vec!["Tank".to_string()]),
+            ("Charlie".to_string(), vec!["Tank".to_string()]),
+        ]);
+        let rules = DistributionRules {
+            hard_avoid: hard_avoid("Alice", "Parlor"),
+            ..Default::default()
+        };
+
+        for _ in 0..50 {
+            let assignments =
+                distribute_work(&names_a, &names_b, &work_areas, &history, &rules).unwrap();
+            assert!(!assignments["Parlor"].contains(&"Alice".to_string()));
+            assert_eq!(assignments["Tank"], names(&["Alice"]));
+        }
+    }
+
+    #[test]
+    fn test_hard_avoid_ignores_task_case() {
+        let names_a = names(&["Alice", "Bob"]);
+
+        let work_areas = HashMap::from([("Toilet A".to_string(), 1)]);
+        let rules = DistributionRules {
+            hard_avoid: hard_avoid("Alice", "toilet_a"),
+            ..Default::default()
+        };
+
+        for _ in 0..20 {
+            let assignments =
+                distribute_work(&names_a, &[], &work_areas, &HashMap::new(), &rules).unwrap();
+            assert_eq!(assignments["Toilet A"], names(&["Bob"]));
+        }
+    }
+
+    #[test]
+    fn test_hard_avoid_leaving_task_short_is_infeasible() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+
+        let rules = DistributionRules {
+            hard_avoid: hard_avoid("Bob", "Toilet A"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_feasibility(
+                &two_groups(&names_a, &names_b),
+                &work_areas,
+                &group_constraints(),
+                &rules
+            ),
+            Err(Infeasibility::Task {
+                task: "Toilet A".to_string(),
+                required: 2,
+                eligible: 1,
+            })
+        );
+        assert!(distribute_work(&names_a, &names_b, &work_areas, &HashMap::new(), &rules).is_err());
+    }
+
+    fn soft_penalty(
+        person: &str,
+        task: &str,
+        weight: u32,
+    ) -> HashMap<String, HashMap<String, u32>> {
+        HashMap::from([(
+            person.to_string(),
+            HashMap::from([(task.to_string(), weight)]),
+        )])
+    }
+
+    #[test]
+    fn test_soft_penalty_discourages_but_does_not_exclude() {
+        let names_a = names(&["Alice", "Bob"]);
+        let rules = DistributionRules {
+            soft_penalties: soft_penalty("Alice", "Parlor", 1000),
+            ..Default::default()
+        };
+
+        // With Bob available, Alice almost never gets the Parlor
+        let one_spot = HashMap::from([("Parlor".to_string(), 1)]);
+        let alice_picked = (0..200)
+            .filter(|_| {
+                distribute_work(&names_a, &[], &one_spot, &HashMap::new(), &rules).unwrap()
+                    ["Parlor"]
+                    .contains(&"Alice".to_string())
+            })
+            .count();
+        assert!(alice_picked <= 5, "Alice picked {} times", alice_picked);
+
+        // When the task needs her, the soft constraint gives way
+        let two_spots = HashMap::from([("Parlor".to_string(), 2)]);
+        let assignments =
+            distribute_work(&names_a, &[], &two_spots, &HashMap::new(), &rules).unwrap();
+        assert!(assignments["Parlor"].contains(&"Alice".to_string()));
+    }
+
+    fn verify_config() -> PeopleConfiguration {
+        use crate::people_config::{GroupConfig, PersonConfig};
+
+        let group = |constraint: &str| GroupConfig {
+            description: String::new(),
+            constraints: vec![constraint.to_string()],
+            soft_weights: HashMap::new(),
+        };
+        let person = |name: &str, group: &str, active: bool| PersonConfig {
+            name: name.to_string(),
+            groups: vec![group.to_string()],
+            active,
+            hard_avoid_tasks: vec![],
+        };
+
+        PeopleConfiguration {
+            groups: HashMap::from([
+                ("A".to_string(), group("cannot_perform_toilet_b")),
+                ("B".to_string(), group("cannot_perform_toilet_a")),
+            ]),
+            people: vec![
+                person("Alice", "A", true),
+                person("Bob", "A", true),
+                person("Dave", "B", true),
+                person("Eve", "B", false),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_verify_assignment_accepts_valid_distribution() {
+        let assignments = assignment_map(&[
+            ("Toilet A", &["Alice"]),
+            ("Toilet B", &["Dave"]),
+            ("Parlor", &["Bob"]),
+        ]);
+
+        assert_eq!(
+            verify_assignment(
+                &assignments,
+                &verify_config(),
+                &DistributionRules::default()
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_verify_assignment_catches_injected_violations() {
+        let assignments = assignment_map(&[
+            ("Toilet A", &["Alice", "Dave"]),
+            ("Parlor", &["Bob", "Bob", "Eve"]),
+        ]);
+
+        assert_eq!(
+            verify_assignment(
+                &assignments,
+                &verify_config(),
+                &DistributionRules::default()
+            ),
+            Err(vec![
+                Violation::DuplicateAssignee {
+                    task: "Parlor".to_string(),
+                    person: "Bob".to_string(),
+                },
+                Violation::UnknownPerson {
+                    task: "Parlor".to_string(),
+                    person: "Eve".to_string(),
+                },
+                Violation::Forbidden {
+                    task: "Toilet A".to_string(),
+                    person: "Dave".to_string(),
+                    groups: "B".to_string(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_verify_assignment_catches_hard_avoid_in_other_case() {
+        let mut config = verify_config();
+        config.people[0].hard_avoid_tasks = vec!["parlor".to_string()];
+        let assignments = assignment_map(&[("Parlor", &["Alice"])]);
+
+        assert_eq!(
+            verify_assignment(&assignments, &config, &DistributionRules::default()),
+            Err(vec![Violation::HardAvoided {
+                task: "Parlor".to_string(),
+                person: "Alice".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_verify_assignment_catches_person_over_cap() {
+        let assignments = assignment_map(&[("Parlor", &["Alice"]), ("Bin", &["Alice", "Bob"])]);
+
+        assert_eq!(
+            verify_assignment(
+                &assignments,
+                &verify_config(),
+                &DistributionRules::default()
+            ),
+            Err(vec![Violation::OverPersonCap {
+                person: "Alice".to_string(),
+                max_per_person: 1,
+                assigned: 2,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_verify_assignment_catches_conflict_pair() {
+        let rules = DistributionRules {
+            conflicts: vec![["Alice".to_string(), "Bob".to_string()]],
+            ..Default::default()
+        };
+        let assignments = assignment_map(&[("Parlor", &["Alice", "Bob"]), ("Bin", &["Dave"])]);
+
+        assert_eq!(
+            verify_assignment(&assignments, &verify_config(), &rules),
+            Err(vec![Violation::Conflict {
+                task: "Parlor".to_string(),
+                first: "Alice".to_string(),
+                second: "Bob".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_verify_assignment_catches_group_minimum() {
+        let rules = DistributionRules {
+            min_from_group: HashMap::from([(
+                "Parlor".to_string(),
+                HashMap::from([("B".to_string(), 1)]),
+            )]),
+            ..Default::default()
+        };
+        let assignments = assignment_map(&[("Parlor", &["Alice", "Bob"]), ("Bin", &["Dave"])]);
+
+        assert_eq!(
+            verify_assignment(&assignments, &verify_config(), &rules),
+            Err(vec![Violation::BelowGroupMinimum {
+                task: "Parlor".to_string(),
+                group: "B".to_string(),
+                min: 1,
+                assigned: 0,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_verify_assignment_catches_group_maximum() {
+        let rules = DistributionRules {
+            max_from_group: HashMap::from([(
+                "Parlor".to_string(),
+                HashMap::from([("A".to_string(), 1)]),
+            )]),
+            ..Default::default()
+        };
+        let assignments = assignment_map(&[("Parlor", &["Alice", "Bob", "Dave"])]);
+
+        assert_eq!(
+            verify_assignment(&assignments, &verify_config(), &rules),
+            Err(vec![Violation::AboveGroupMaximum {
+                task: "Parlor".to_string(),
+                group: "A".to_string(),
+                max: 1,
+                assigned: 2,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_soft_weight_softens_built_in_constraint() {
+        let mut config = verify_config();
+        config
+            .groups
+            .get_mut("A")
+            .unwrap()
+            .soft_weights
+            .insert("cannot_perform_toilet_b".to_string(), 1);
+        assert!(config.validate().is_ok());
+
+        let groups = two_groups(&names(&["Alice", "Bob"]), &names(&["Dave"]));
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet B".to_string(), 2);
+
+        // Group A may now take Toilet B, only less often
+        let constraints = configured_constraints(&config, &work_areas);
+        assert_eq!(
+            check_feasibility(
+                &groups,
+                &work_areas,
+                &constraints,
+                &DistributionRules::default()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            verify_assignment(
+                &assignment_map(&[("Toilet B", &["Alice", "Dave"])]),
+                &config,
+                &DistributionRules::default()
+            ),
+            Ok(())
+        );
+
+        // Without the weight the built-in exclusion still applies
+        let constraints = configured_constraints(&verify_config(), &work_areas);
+        assert_eq!(
+            check_feasibility(
+                &groups,
+                &work_areas,
+                &constraints,
+                &DistributionRules::default()
+            ),
+            Err(Infeasibility::Task {
+                task: "Toilet B".to_string(),
+                required: 2,
+                eligible: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_feasibility_ok() {
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+        work_areas.insert("Toilet B".to_string(), 1);
+        work_areas.insert("Parlor".to_string(), 1);
+
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules::default(),
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_check_feasibility_total_shortfall() {
+        let names_a = names(&["Alice"]);
+        let names_b = names(&["Charlie"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Parlor".to_string(), 3);
+
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules::default(),
+        );
+        assert_eq!(
+            result,
+            Err(Infeasibility::Total {
+                required: 3,
+                available: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_feasibility_task_shortfall() {
+        let names_a = names(&["Alice"]);
+        let names_b = names(&["Charlie", "Dave", "Eve"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules::default(),
+        );
+        assert_eq!(
+            result,
+            Err(Infeasibility::Task {
+                task: "Toilet A".to_string(),
+                required: 2,
+                eligible: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_feasibility_counts_multi_group_people_once() {
+        // Bob floats between groups but can still only fill one spot, and neither toilet
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Bob", "Charlie"]);
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &group_constraints(),
+            &DistributionRules::default(),
+        );
+        assert_eq!(
+            result,
+            Err(Infeasibility::Task {
+                task: "Toilet A".to_string(),
+                required: 2,
+                eligible: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_feasibility_group_shortfall() {
+        // Each toilet alone fits, but together they need more of group A than exists
+        let names_a = names(&["Alice", "Bob"]);
+        let names_b = names(&["Charlie", "Dave"]);
+
+        let mut constraints = group_constraints();
+        constraints.insert(
+            "B".to_string(),
+            vec!["Toilet A".to_string(), "Tank".to_string()],
+        );
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+        work_areas.insert("Tank".to_string(), 1);
+
+        let result = check_feasibility(
+            &two_groups(&names_a, &names_b),
+            &work_areas,
+            &constraints,
+            &DistributionRules::default(),
+        );
+        assert_eq!(
+            result,
+            Err(Infeasibility::Group {
+                groups: vec!["A".to_string()],
+                required: 3,
+                available: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_feasibility_many_membership_combinations() {
+        // 70 distinct group sets; enumerating subsets of them would never finish
+        let groups: GroupMembers = (0..70)
+            .map(|i| (format!("G{}", i), vec![format!("Person {}", i)]))
+            .collect();
+        let constraints: HashMap<String, Vec<String>> = (2..70)
+            .map(|i| {
+                (
+                    format!("G{}", i),
+                    vec!["Toilet A".to_string(), "Tank".to_string()],
+                )
+            })
+            .collect();
+
+        let mut work_areas = HashMap::new();
+        work_areas.insert("Toilet A".to_string(), 2);
+        work_areas.insert("Tank".to_string(), 1);
+        work_areas.insert("Parlor".to_string(), 5);
+
+        let result = check_feasibility(
+            &groups,
+            &work_areas,
+            &constraints,
+            &DistributionRules::default(),
+        );
+        assert_eq!(
+            result,
+            Err(Infeasibility::Group {
+                groups: vec!["G0".to_string(), "G1".to_string()],
+                required: 3,
+                available: 2
+            })
+        );
+
+        work_areas.insert("Tank".to_string(), 0);
+        let result = check_feasibility(
+            &groups,
+            &work_areas,
+            &constraints,
+            &DistributionRules::default(),
+        );
+        assert_eq!(result, Ok(()));
+    }
 }
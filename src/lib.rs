@@ -2,4 +2,10 @@
 //!
 //! This library provides modules for managing work group assignments.
 
+pub mod config;
+pub mod db;
+pub mod group;
+pub mod models;
+pub mod output;
 pub mod people_config;
+pub mod schema;
@@ -1,16 +1,15 @@
-mod config;
-mod db;
-mod group;
-mod models;
-mod output;
-mod people_config;
-mod schema;
-
 use anyhow::Context;
+use chrono::Utc;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::collections::HashMap;
 use std::env;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::time::Duration;
 use tracing::{error, info, warn};
+use work_group_generator::people_config::PeopleConfiguration;
+use work_group_generator::{config, db, group, output};
 
 fn set_github_output(should_notify: bool, env_path: Option<&str>) {
     let path = match env_path {
@@ -36,22 +35,179 @@ fn set_github_output(should_notify: bool, env_path: Option<&str>) {
     }
 }
 
+/// Whether this run's result is worth a notification. With `notify_on_change_only`,
+/// a distribution identical to the previous cycle is saved but not announced.
+fn should_notify(diff: Option<&group::AssignmentDiff>, notify_on_change_only: bool) -> bool {
+    !notify_on_change_only || diff.is_none_or(|d| d.has_changes())
+}
+
+fn write_calendar(
+    path: &str,
+    assignments: &HashMap<String, Vec<String>>,
+    people_config: &PeopleConfiguration,
+) {
+    let ics = output::assignments_to_ics(assignments, Utc::now().date_naive(), people_config);
+    match fs::write(path, ics) {
+        Ok(()) => info!("📅 Calendar feed written to {}", path),
+        Err(e) => error!("Failed to write calendar feed to {}: {}", path, e),
+    }
+}
+
+/// The value of `--seed <n>`, if given. Seeded runs reproduce the same distribution
+/// for the same people, history and settings.
+fn seed_arg(args: &[String]) -> anyhow::Result<Option<i64>> {
+    let Some(pos) = args.iter().position(|arg| arg == "--seed") else {
+        return Ok(None);
+    };
+    let value = args.get(pos + 1).context("--seed needs a value")?;
+    let seed = value
+        .parse::<u64>()
+        .with_context(|| format!("Invalid --seed value '{}'", value))?;
+    let seed = i64::try_from(seed).context("--seed must be at most 9223372036854775807")?;
+    Ok(Some(seed))
+}
+
+/// Builds the connection pool from settings, retrying while the database is unreachable.
+fn connect(settings: &config::Settings) -> Result<db::DbPool, diesel::r2d2::PoolError> {
+    let retry = db::RetryPolicy {
+        attempts: settings.db_connect_attempts,
+        base_delay: Duration::from_millis(settings.db_connect_base_delay_ms),
+    };
+    db::establish_connection(
+        &settings.database_url,
+        settings.db_pool_max_size,
+        Duration::from_secs(settings.db_pool_timeout_secs),
+        &retry,
+    )
+}
+
+/// One line of the `--self-test` checklist: what was checked and what happened.
+struct Check {
+    name: &'static str,
+    result: Result<String, String>,
+}
+
+/// Confirms configuration, people and the database are usable without generating anything.
+/// Checks that depend on an earlier failed one are skipped.
+fn self_test(settings: Result<config::Settings, ::config::ConfigError>) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let settings = match settings {
+        Ok(s) => s,
+        Err(e) => {
+            checks.push(Check {
+                name: "Configuration",
+                result: Err(e.to_string()),
+            });
+            return checks;
+        }
+    };
+    checks.push(Check {
+        name: "Configuration",
+        result: Ok(format!("{} task(s)", settings.work_assignments.len())),
+    });
+
+    checks.push(Check {
+        name: "People configuration",
+        result: PeopleConfiguration::load()
+            .map(|c| format!("{} active people", c.active_people_count()))
+            .map_err(|e| e.to_string()),
+    });
+
+    let conn = connect(&settings)
+        .map_err(|e| e.to_string())
+        .and_then(|pool| pool.get().map_err(|e| e.to_string()));
+    let mut conn = match conn {
+        Ok(conn) => {
+            checks.push(Check {
+                name: "Database connection",
+                result: Ok("connected".to_string()),
+            });
+            conn
+        }
+        Err(e) => {
+            checks.push(Check {
+                name: "Database connection",
+                result: Err(e),
+            });
+            return checks;
+        }
+    };
+
+    checks.push(Check {
+        name: "Schedule check",
+        result: db::should_run(&mut conn)
+            .map(|due| if due { "due" } else { "not due" }.to_string())
+            .map_err(|e| e.to_string()),
+    });
+
+    checks
+}
+
 fn main() -> anyhow::Result<()> {
     // 1. Initialize Logging
     tracing_subscriber::fmt::init();
     info!("🚀 Starting Work Group Generator...");
+    let args: Vec<String> = env::args().collect();
+    let notify_on_change_only = args.iter().any(|arg| arg == "--notify-on-change-only");
+    let force = args.iter().any(|arg| arg == "--force");
+    let seed = seed_arg(&args)?;
+
+    if args.iter().any(|arg| arg == "--self-test") {
+        info!("🩺 Running self-test...");
+        let checks = self_test(config::Settings::new());
+        for check in &checks {
+            match &check.result {
+                Ok(detail) => info!("✅ {}: {}", check.name, detail),
+                Err(e) => error!("❌ {}: {}", check.name, e),
+            }
+        }
+        let failed = checks.iter().filter(|c| c.result.is_err()).count();
+        if failed > 0 {
+            anyhow::bail!("Self-test failed: {} check(s) failed", failed);
+        }
+        info!("🎉 Self-test passed.");
+        return Ok(());
+    }
 
     // 2. Load Configuration
     let settings = config::Settings::new().context("Failed to load configuration")?;
     info!("✅ Configuration loaded.");
 
     // 3. Connect to DB
-    let pool = db::establish_connection(&settings.database_url);
-    let mut conn = pool.get().context("Failed to get DB connection")?;
+    let pool = match connect(&settings) {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!(
+                "🔥 CRITICAL ERROR: Could not connect to the database: {}",
+                e
+            );
+            set_github_output(false, settings.github_env_path.as_deref());
+            anyhow::bail!("Could not connect to the database: {}", e);
+        }
+    };
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("🔥 CRITICAL ERROR: Failed to get DB connection: {}", e);
+            set_github_output(false, settings.github_env_path.as_deref());
+            anyhow::bail!("Failed to get DB connection: {}", e);
+        }
+    };
 
     // 4. Check Schedule (14 day rule)
-    match db::should_run(&mut conn) {
-        Ok(true) => info!("✅ It has been 14+ days (or first run). Proceeding."),
+    // `forced` records whether --force actually overrode the schedule
+    let forced = match db::should_run(&mut conn) {
+        Ok(true) => {
+            info!("✅ It has been 14+ days (or first run). Proceeding.");
+            false
+        }
+        Ok(false) if force => {
+            warn!(
+                "⏩ It has NOT been 14 days since the last run, but --force was given. Proceeding."
+            );
+            true
+        }
         Ok(false) => {
             info!("⏳ It has NOT been 14 days since the last run. Skipping.");
             set_github_output(false, settings.github_env_path.as_deref());
@@ -62,70 +218,310 @@ fn main() -> anyhow::Result<()> {
             set_github_output(false, settings.github_env_path.as_deref());
             return Err(anyhow::anyhow!("Error checking schedule: {}", e));
         }
-    }
+    };
 
     let work_areas = &settings.work_assignments;
     info!("📋 Work assignments loaded: {:?}", work_areas.keys());
 
     // 5. Fetch People
-    let (names_a, names_b, name_to_id) =
-        db::fetch_people(&mut conn).context("Failed to fetch people")?;
+    let people_config =
+        PeopleConfiguration::load().context("Failed to load people configuration")?;
+    let (groups, name_to_id) = db::fetch_people(&mut conn).context("Failed to fetch people")?;
     info!(
-        "👥 Fetched {} active people (Group A: {}, Group B: {})",
-        names_a.len() + names_b.len(),
-        names_a.len(),
-        names_b.len()
+        "👥 Fetched {} active people ({})",
+        name_to_id.len(),
+        db::group_counts(&groups)
     );
+    match db::fetch_people_with_last_assignment(&mut conn) {
+        Ok(stats) => {
+            let never_assigned: Vec<&str> = stats
+                .iter()
+                .filter(|s| s.last_assigned_at.is_none())
+                .filter(|s| name_to_id.contains_key(&s.person.name))
+                .map(|s| s.person.name.as_str())
+                .collect();
+            if !never_assigned.is_empty() {
+                info!("🆕 Never assigned before: {}", never_assigned.join(", "));
+            }
+        }
+        Err(e) => warn!("Could not load last assignment dates: {}", e),
+    }
 
     // 6. Fetch History
     info!("🔍 Reading assignment history from DB...");
-    let history = db::fetch_history(&mut conn, &name_to_id).context("Failed to fetch history")?;
-
-    // 7. Generate Assignments (Start Retry Loop)
-    info!("🔄 Generating new work distribution...");
-    let mut final_assignments = None;
-    const MAX_ATTEMPTS: u32 = 500;
-
-    for attempt in 1..=MAX_ATTEMPTS {
-        match group::distribute_work(&names_a, &names_b, work_areas, &history) {
-            Ok(new_assignments) => {
+    let cycles =
+        db::fetch_history_detailed(&mut conn, &name_to_id).context("Failed to fetch history")?;
+    let history = db::recent_history(&cycles, settings.history_window);
+    match cycles.last() {
+        Some(last) => info!(
+            "📚 Found {} previous cycles (most recent on {}).",
+            cycles.len(),
+            last.assigned_at
+        ),
+        None => info!("📚 No previous cycles found."),
+    }
+    match db::fetch_fairness_trend(&mut conn, 5) {
+        Ok(trend) => {
+            for cycle in &trend {
                 info!(
-                    "✅ Successfully found a valid assignment on attempt {}!",
-                    attempt
+                    "⚖️ Cycle {}: {} assignments over {} people, variance {:.2}",
+                    cycle.assigned_at, cycle.assignments, cycle.people, cycle.variance
                 );
-                final_assignments = Some(new_assignments);
-                break;
             }
-            Err(_) => continue,
         }
+        Err(e) => warn!("Could not compute fairness trend: {}", e),
     }
 
-    // 8. Save and Output
-    if let Some(assignments) = final_assignments {
+    // 7. Check Feasibility (no point retrying an impossible request)
+    if let Err(e) = people_config.check_hard_avoid_tasks(work_areas.keys()) {
+        error!("🔥 CRITICAL ERROR: Invalid hard-avoided task: {}", e);
+        set_github_output(false, settings.github_env_path.as_deref());
+        anyhow::bail!("Invalid hard-avoided task: {}", e);
+    }
+    let rules = group::DistributionRules {
+        locks: settings.locks.clone(),
+        max_per_person: settings.max_per_person,
+        min_per_person: settings.min_per_person,
+        same_task_gap: (settings.min_days_between_same_task > 0).then(|| group::SameTaskGap {
+            min_days: settings.min_days_between_same_task,
+            last_done: db::last_task_dates(&cycles),
+            now: Utc::now().naive_utc(),
+            relax_oldest_first: settings.relax_same_task_gap,
+        }),
+        conflicts: settings.conflicts.clone(),
+        hard_avoid: people_config.hard_avoids(),
+        soft_penalties: people_config.soft_penalties(work_areas.keys()),
+        min_from_group: settings.min_from_group.clone(),
+        max_from_group: settings.max_from_group.clone(),
+    };
+    let constraints = group::configured_constraints(&people_config, work_areas);
+    if let Err(e) = group::check_feasibility(&groups, work_areas, &constraints, &rules) {
+        error!(
+            "🔥 CRITICAL ERROR: Requested distribution is infeasible: {}",
+            e
+        );
+        set_github_output(false, settings.github_env_path.as_deref());
+        anyhow::bail!("Requested distribution is infeasible: {}", e);
+    }
+    if let Err(e) = group::validate_locks(&groups, work_areas, &constraints, &rules) {
+        error!("🔥 CRITICAL ERROR: Invalid assignment lock: {}", e);
+        set_github_output(false, settings.github_env_path.as_deref());
+        anyhow::bail!("Invalid assignment lock: {}", e);
+    }
+    if !rules.locks.is_empty() {
+        info!("🔒 {} assignment lock(s) configured.", rules.locks.len());
+    }
+    if !rules.conflicts.is_empty() {
+        info!("🚧 {} conflict pair(s) configured.", rules.conflicts.len());
+    }
+
+    // 8. Generate Assignments (Start Retry Loop)
+    let max_attempts = settings.max_attempts;
+    info!(
+        "🔄 Generating new work distribution (up to {} attempts)...",
+        max_attempts
+    );
+    let mut rng: Box<dyn RngCore> = match seed {
+        Some(seed) => {
+            info!("🎲 Using seed {}.", seed);
+            Box::new(StdRng::seed_from_u64(seed.unsigned_abs()))
+        }
+        None => Box::new(rand::thread_rng()),
+    };
+    let final_assignments = group::distribute_with_retries_with_rng(
+        &groups,
+        work_areas,
+        &constraints,
+        &history,
+        &rules,
+        max_attempts,
+        &mut *rng,
+    );
+    if let Some((attempt, _)) = &final_assignments {
+        info!(
+            "✅ Successfully found a valid assignment on attempt {}!",
+            attempt
+        );
+    }
+
+    // 9. Save and Output
+    if let Some((attempts, assignments)) = final_assignments {
         output::print_assignments(&assignments);
-        if let Err(e) = db::save_assignments(&mut conn, &assignments, &name_to_id) {
-            error!(
-                "🔥 CRITICAL ERROR: Failed to save new assignments to DB: {}",
-                e
-            );
+        output::print_assignments_grouped(&assignments, &people_config);
+        let diff = cycles
+            .last()
+            .map(|previous| group::diff_assignments(&previous.tasks, &assignments));
+        match &diff {
+            Some(diff) => output::print_changes(diff),
+            None => info!("📭 First cycle, nothing to compare against."),
+        }
+        if let Err(violations) = group::verify_assignment(&assignments, &people_config, &rules) {
+            for v in &violations {
+                error!("🔥 Constraint violation: {}", v);
+            }
             set_github_output(false, settings.github_env_path.as_deref());
-            return Err(anyhow::anyhow!("Failed to save assignments: {}", e));
-        } else {
-            info!("💾 Assignment history has been saved to the database.");
-            set_github_output(true, settings.github_env_path.as_deref());
+            anyhow::bail!(
+                "Generated distribution failed verification with {} violation(s); nothing was saved",
+                violations.len()
+            );
+        }
+        let run_meta = db::RunMeta {
+            attempts,
+            seed,
+            forced,
+        };
+        match db::save_assignments(&mut conn, &assignments, &name_to_id, &run_meta) {
+            Err(e) => {
+                error!(
+                    "🔥 CRITICAL ERROR: Failed to save new assignments to DB: {}",
+                    e
+                );
+                set_github_output(false, settings.github_env_path.as_deref());
+                return Err(anyhow::anyhow!("Failed to save assignments: {}", e));
+            }
+            Ok(run_id) => {
+                info!(
+                    "💾 Assignment history has been saved to the database (run #{}).",
+                    run_id
+                );
+                if let Some(path) = settings.calendar_path.as_deref() {
+                    write_calendar(path, &assignments, &people_config);
+                }
+                let notify = should_notify(diff.as_ref(), notify_on_change_only);
+                if !notify {
+                    info!("🔕 Nothing changed since the last cycle, skipping notification.");
+                }
+                set_github_output(notify, settings.github_env_path.as_deref());
+            }
         }
     } else {
         error!(
             "🔥 CRITICAL ERROR: Could not find a valid assignment after {} attempts.",
-            MAX_ATTEMPTS
+            max_attempts
         );
         set_github_output(false, settings.github_env_path.as_deref());
         anyhow::bail!(
             "Could not find a valid assignment after {} attempts.",
-            MAX_ATTEMPTS
+            max_attempts
         );
     }
 
     info!("🎉 Done.");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignments(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(task, people)| {
+                (
+                    task.to_string(),
+                    people.iter().map(|p| p.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn notify_output(diff: Option<&group::AssignmentDiff>, notify_on_change_only: bool) -> String {
+        let path = env::temp_dir().join(format!(
+            "vividshift_github_env_{}_{}",
+            std::process::id(),
+            notify_on_change_only
+        ));
+        fs::remove_file(&path).ok();
+
+        let notify = should_notify(diff, notify_on_change_only);
+        set_github_output(notify, path.to_str());
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).ok();
+        written
+    }
+
+    #[test]
+    fn test_notify_on_change_only() {
+        let previous = assignments(&[("Parlor", &["Alice", "Bob"]), ("Bin", &["Charlie"])]);
+        let identical = group::diff_assignments(&previous, &previous);
+        let changed = group::diff_assignments(
+            &previous,
+            &assignments(&[("Parlor", &["Alice", "Charlie"]), ("Bin", &["Bob"])]),
+        );
+
+        assert_eq!(
+            notify_output(Some(&identical), true),
+            "SHOULD_NOTIFY=false\n"
+        );
+        assert_eq!(notify_output(Some(&changed), true), "SHOULD_NOTIFY=true\n");
+    }
+
+    fn settings_for(database_url: &str) -> config::Settings {
+        ::config::Config::builder()
+            .set_override("database_url", database_url)
+            .unwrap()
+            .set_override("db_connect_attempts", 1)
+            .unwrap()
+            .set_override("db_pool_timeout_secs", 1)
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    fn check_names(checks: &[Check]) -> Vec<(&str, bool)> {
+        checks.iter().map(|c| (c.name, c.result.is_ok())).collect()
+    }
+
+    #[test]
+    fn test_self_test_unreachable_database() {
+        let checks = self_test(Ok(settings_for("postgres://nobody@127.0.0.1:1/missing")));
+        let results = check_names(&checks);
+
+        assert_eq!(results[0], ("Configuration", true));
+        assert_eq!(results.last(), Some(&("Database connection", false)));
+        assert!(!results.iter().any(|(name, _)| *name == "Schedule check"));
+    }
+
+    /// Needs a migrated scratch database; run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_self_test_reachable_database() {
+        let url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+
+        let checks = self_test(Ok(settings_for(&url)));
+        let results = check_names(&checks);
+
+        assert!(results.contains(&("Database connection", true)));
+        assert!(results.contains(&("Schedule check", true)));
+    }
+
+    #[test]
+    fn test_self_test_bad_configuration() {
+        let checks = self_test(Err(::config::ConfigError::Message("broken".to_string())));
+
+        assert_eq!(check_names(&checks), vec![("Configuration", false)]);
+    }
+
+    #[test]
+    fn test_notify_without_flag_or_history() {
+        let previous = assignments(&[("Parlor", &["Alice"])]);
+        let identical = group::diff_assignments(&previous, &previous);
+
+        assert!(should_notify(Some(&identical), false));
+        assert!(should_notify(None, true));
+    }
+
+    #[test]
+    fn test_seed_arg() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(seed_arg(&args(&["bin"])).unwrap(), None);
+        assert_eq!(seed_arg(&args(&["bin", "--seed", "42"])).unwrap(), Some(42));
+        assert!(seed_arg(&args(&["bin", "--seed"])).is_err());
+        assert!(seed_arg(&args(&["bin", "--seed", "-1"])).is_err());
+        assert!(seed_arg(&args(&["bin", "--seed", "18446744073709551615"])).is_err());
+    }
+}
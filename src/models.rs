@@ -1,4 +1,4 @@
-use crate::schema::{assignments, people};
+use crate::schema::{assignments, people, runs};
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 
@@ -26,6 +26,7 @@ pub struct Assignment {
     pub person_id: i32,
     pub task_name: String,
     pub assigned_at: NaiveDateTime,
+    pub run_id: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -34,4 +35,24 @@ pub struct NewAssignment<'a> {
     pub person_id: i32,
     pub task_name: &'a str,
     pub assigned_at: NaiveDateTime,
+    pub run_id: Option<i32>,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = runs)]
+pub struct Run {
+    pub id: i32,
+    pub created_at: NaiveDateTime,
+    pub attempts: i32,
+    pub seed: Option<i64>,
+    pub forced: bool,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = runs)]
+pub struct NewRun {
+    pub created_at: NaiveDateTime,
+    pub attempts: i32,
+    pub seed: Option<i64>,
+    pub forced: bool,
 }
@@ -1,6 +1,9 @@
 // src/output.rs
 
-use std::collections::HashMap;
+use crate::group::{self, AssignmentDiff};
+use crate::people_config::PeopleConfiguration;
+use chrono::{Days, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
 use tracing::info;
 
 /// Prints the assignments in a clean, formatted way.
@@ -18,3 +21,300 @@ pub fn print_assignments(assignments: &HashMap<String, Vec<String>>) {
         info!("➡️  {:<12}: {}", area, sorted_people.join(", "));
     }
 }
+
+/// Prints each task with assignees annotated by group, a per-task group count,
+/// and a warning line for anyone whose group constraints forbid the task.
+pub fn print_assignments_grouped(
+    assignments: &HashMap<String, Vec<String>>,
+    people_config: &PeopleConfiguration,
+) {
+    info!("🧩 Group Breakdown");
+    for line in format_assignments_grouped(assignments, people_config) {
+        info!("{}", line);
+    }
+}
+
+fn format_assignments_grouped(
+    assignments: &HashMap<String, Vec<String>>,
+    people_config: &PeopleConfiguration,
+) -> Vec<String> {
+    let constraints = group::built_in_constraints(people_config);
+    let mut group_ids: Vec<&String> = people_config.get_group_ids().collect();
+    group_ids.sort();
+
+    let mut sorted_areas: Vec<_> = assignments.keys().collect();
+    sorted_areas.sort();
+
+    let mut lines = Vec::new();
+    for area in sorted_areas {
+        let mut sorted_people = assignments[area].clone();
+        sorted_people.sort();
+
+        let mut counts: BTreeMap<&String, usize> = group_ids.iter().map(|g| (*g, 0)).collect();
+        let mut annotated = Vec::new();
+        let mut warnings = Vec::new();
+
+        for name in &sorted_people {
+            match people_config.find_person(name) {
+                Some(person) => {
+                    for group in &person.groups {
+                        if let Some(count) = counts.get_mut(group) {
+                            *count += 1;
+                        }
+                    }
+                    annotated.push(format!("{} ({})", name, person.groups.join("/")));
+                    if group::is_forbidden(&constraints, people_config, person, area) {
+                        warnings.push(format!(
+                            "⚠️  {:<12}: {} ({}) is not allowed on this task",
+                            area,
+                            name,
+                            person.groups.join("/")
+                        ));
+                    }
+                }
+                None => annotated.push(format!("{} (?)", name)),
+            }
+        }
+
+        let summary: Vec<String> = counts
+            .iter()
+            .map(|(group, count)| format!("{}: {}", group, count))
+            .collect();
+        lines.push(format!(
+            "🧩 {:<12}: {} [{}]",
+            area,
+            annotated.join(", "),
+            summary.join(", ")
+        ));
+        lines.extend(warnings);
+    }
+
+    lines
+}
+
+/// Prints who is new on each task compared to the previous cycle.
+/// Lines are tagged with 🆕 so the notification workflow can pick them up.
+pub fn print_changes(diff: &AssignmentDiff) {
+    if !diff.has_changes() {
+        info!("🔁 Assignments are identical to the previous cycle.");
+        return;
+    }
+
+    info!("🆕 New this cycle");
+    for (task, task_diff) in &diff.tasks {
+        if !task_diff.added.is_empty() {
+            info!("🆕 {:<12}: {}", task, task_diff.added.join(", "));
+        }
+    }
+}
+
+/// Renders the assignments as an iCalendar (RFC 5545) feed with one all-day event per task.
+/// UIDs are derived from the task and date so re-exporting the same cycle updates, rather than duplicates, events.
+pub fn assignments_to_ics(
+    assignments: &HashMap<String, Vec<String>>,
+    date: NaiveDate,
+    people_config: &PeopleConfiguration,
+) -> String {
+    let day = date.format("%Y%m%d").to_string();
+    let next_day = date
+        .checked_add_days(Days::new(1))
+        .unwrap_or(date)
+        .format("%Y%m%d")
+        .to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//VividShift//Work Group Generator//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    let mut sorted_areas: Vec<_> = assignments.keys().collect();
+    sorted_areas.sort();
+
+    for area in sorted_areas {
+        let mut sorted_people = assignments[area].clone();
+        sorted_people.sort();
+        let assignees: Vec<String> = sorted_people
+            .iter()
+            .map(|name| match people_config.find_person(name) {
+                Some(person) => format!("{} ({})", name, person.groups.join("/")),
+                None => name.clone(),
+            })
+            .collect();
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}-{}@vividshift", ics_uid_slug(area), day));
+        lines.push(format!("DTSTAMP:{}T000000Z", day));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", day));
+        lines.push(format!("DTEND;VALUE=DATE:{}", next_day));
+        lines.push(format!("SUMMARY:{}", ics_escape(area)));
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            ics_escape(&format!("Assigned: {}", assignees.join(", ")))
+        ));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| ics_fold(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+/// Escapes TEXT values per RFC 5545 section 3.3.11.
+fn ics_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Lowercases a task name and replaces anything but ASCII alphanumerics with '-'.
+fn ics_uid_slug(task: &str) -> String {
+    task.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Folds content lines longer than 75 octets, never splitting a UTF-8 character.
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::people_config::{GroupConfig, PersonConfig};
+
+    fn people_config() -> PeopleConfiguration {
+        let mut groups = HashMap::new();
+        groups.insert(
+            "A".to_string(),
+            GroupConfig {
+                description: "Group A".to_string(),
+                constraints: vec!["cannot_perform_toilet_b".to_string()],
+                soft_weights: HashMap::new(),
+            },
+        );
+        groups.insert(
+            "B".to_string(),
+            GroupConfig {
+                description: "Group B".to_string(),
+                constraints: vec!["cannot_perform_toilet_a".to_string()],
+                soft_weights: HashMap::new(),
+            },
+        );
+
+        let person = |name: &str, group: &str| PersonConfig {
+            name: name.to_string(),
+            groups: vec![group.to_string()],
+            active: true,
+            hard_avoid_tasks: vec![],
+        };
+
+        PeopleConfiguration {
+            groups,
+            people: vec![person("Alice", "A"), person("Dave", "B")],
+        }
+    }
+
+    #[test]
+    fn test_format_assignments_grouped() {
+        let mut assignments = HashMap::new();
+        assignments.insert(
+            "Parlor".to_string(),
+            vec!["Dave".to_string(), "Alice".to_string()],
+        );
+        assignments.insert("Toilet A".to_string(), vec!["Dave".to_string()]);
+
+        let lines = format_assignments_grouped(&assignments, &people_config());
+
+        assert_eq!(
+            lines,
+            vec![
+                "🧩 Parlor      : Alice (A), Dave (B) [A: 1, B: 1]",
+                "🧩 Toilet A    : Dave (B) [A: 0, B: 1]",
+                "⚠️  Toilet A    : Dave (B) is not allowed on this task",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_assignments_grouped_warns_on_built_in_constraint() {
+        // Group A declares nothing, so only the built-in rule keeps it off Toilet B
+        let mut config = people_config();
+        config.groups.get_mut("A").unwrap().constraints.clear();
+        let mut assignments = HashMap::new();
+        assignments.insert("Toilet B".to_string(), vec!["Alice".to_string()]);
+
+        let lines = format_assignments_grouped(&assignments, &config);
+
+        assert_eq!(
+            lines,
+            vec![
+                "🧩 Toilet B    : Alice (A) [A: 1, B: 0]",
+                "⚠️  Toilet B    : Alice (A) is not allowed on this task",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assignments_to_ics_has_one_event_per_task() {
+        let mut assignments = HashMap::new();
+        assignments.insert("Parlor".to_string(), vec!["Alice".to_string()]);
+        assignments.insert(
+            "Toilet A".to_string(),
+            vec!["Bob".to_string(), "Carol".to_string()],
+        );
+        let date = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+
+        let ics = assignments_to_ics(&assignments, date, &people_config());
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert_eq!(lines.iter().filter(|l| **l == "BEGIN:VEVENT").count(), 2);
+        assert_eq!(lines.iter().filter(|l| **l == "END:VEVENT").count(), 2);
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+
+        assert!(lines.contains(&"UID:toilet-a-20240331@vividshift"));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20240331"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20240401"));
+        assert!(lines.contains(&"DESCRIPTION:Assigned: Alice (A)"));
+        assert!(lines.contains(&"DESCRIPTION:Assigned: Bob\\, Carol"));
+    }
+
+    #[test]
+    fn test_ics_escape_and_fold() {
+        assert_eq!(ics_escape("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+
+        let long = format!("SUMMARY:{}", "x".repeat(100));
+        let folded = ics_fold(&long);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].len(), 75);
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), long);
+    }
+}
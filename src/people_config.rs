@@ -17,17 +17,27 @@
 //! ```no_run
 //! use work_group_generator::people_config::PeopleConfiguration;
 //!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! let config = PeopleConfiguration::load()?;
 //! let group_a_people = config.get_people_by_group("A");
 //! let active_people = config.get_active_people();
+//! # Ok(())
+//! # }
 //! ```
 //!
+//! # Multi-group membership
+//!
+//! A person may list several groups (`groups = ["A", "B"]`); the legacy
+//! single `group = "A"` form is still accepted. Such a person appears in every
+//! group filter they belong to and is bound by the **union** of those groups'
+//! constraints, so membership can only ever narrow the tasks they may take.
+//!
 //! # Error Handling
 //!
 //! All operations return `Result` types with descriptive errors using `thiserror`.
 //! No panics or unwraps are used in production code paths.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
@@ -72,6 +82,22 @@ pub enum ValidationError {
     /// No people defined in configuration
     #[error("Configuration must contain at least one person")]
     EmptyConfiguration,
+
+    /// Person does not belong to any group
+    #[error("Person '{0}' must belong to at least one group")]
+    NoGroups(String),
+
+    /// A soft weight names a constraint the group does not have
+    #[error("Group '{group}' sets a soft weight for unknown constraint '{constraint}'")]
+    UnknownSoftConstraint { group: String, constraint: String },
+
+    /// A soft weight of zero, which would make the constraint no constraint at all
+    #[error("Group '{group}' sets soft weight 0 for '{constraint}'; weights must be at least 1")]
+    ZeroSoftWeight { group: String, constraint: String },
+
+    /// A hard-avoided task is not one of the configured tasks
+    #[error("Person '{person}' hard-avoids unknown task '{task}'")]
+    UnknownHardAvoidTask { person: String, task: String },
 }
 
 /// Configuration for a single group
@@ -84,6 +110,42 @@ pub struct GroupConfig {
     /// Example: ["cannot_perform_toilet_b"]
     #[serde(default)]
     pub constraints: Vec<String>,
+
+    /// Constraints that are soft, mapped to their penalty weight
+    /// Any constraint not listed here is hard
+    #[serde(default)]
+    pub soft_weights: HashMap<String, u32>,
+}
+
+/// How strictly a group constraint is enforced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The constraint is never broken
+    Hard,
+    /// The constraint only discourages; higher weights discourage more
+    Soft { weight: u32 },
+}
+
+impl GroupConfig {
+    /// Severity of one of this group's constraints
+    pub fn severity(&self, constraint: &str) -> Severity {
+        match self.soft_weights.get(constraint) {
+            Some(&weight) => Severity::Soft { weight },
+            None => Severity::Hard,
+        }
+    }
+
+    /// True if the group lists a `cannot_perform_<task>` constraint for the task, at any severity
+    pub fn constrains_task(&self, task: &str) -> bool {
+        self.constraints.contains(&task_constraint(task))
+    }
+
+    /// Constraints enforced as hard exclusions
+    fn hard_constraints(&self) -> impl Iterator<Item = &String> {
+        self.constraints
+            .iter()
+            .filter(|c| self.severity(c) == Severity::Hard)
+    }
 }
 
 /// Configuration for a single person
@@ -92,18 +154,58 @@ pub struct PersonConfig {
     /// Person's full name (must be unique)
     pub name: String,
 
-    /// Group identifier (must reference a defined group)
-    pub group: String,
+    /// Group identifiers (each must reference a defined group)
+    /// Accepts either `groups = ["A", "B"]` or the legacy `group = "A"`
+    #[serde(alias = "group", deserialize_with = "deserialize_groups")]
+    pub groups: Vec<String>,
 
     /// Whether the person is currently active
     #[serde(default = "default_active")]
     pub active: bool,
+
+    /// Tasks this person must never be given, regardless of group (e.g. an allergy)
+    #[serde(default)]
+    pub hard_avoid_tasks: Vec<String>,
+}
+
+impl PersonConfig {
+    /// Check whether this person belongs to the given group
+    pub fn in_group(&self, group: &str) -> bool {
+        self.groups.iter().any(|g| g == group)
+    }
+
+    /// Check whether the person listed the task in their hard avoids
+    ///
+    /// Matches the way `cannot_perform_<task>` constraints do, ignoring case and
+    /// treating spaces and underscores alike, so "toilet_b" avoids "Toilet B".
+    pub fn avoids_task(&self, task: &str) -> bool {
+        let key = task_key(task);
+        self.hard_avoid_tasks.iter().any(|t| task_key(t) == key)
+    }
 }
 
 fn default_active() -> bool {
     true
 }
 
+/// Accept a single group string or a list of groups
+fn deserialize_groups<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(group) => vec![group],
+        OneOrMany::Many(groups) => groups,
+    })
+}
+
 /// Root configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PeopleConfiguration {
@@ -132,7 +234,11 @@ impl PeopleConfiguration {
     /// # Example
     ///
     /// ```no_run
+    /// # use work_group_generator::people_config::PeopleConfiguration;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let config = PeopleConfiguration::load()?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn load() -> Result<Self, ConfigError> {
         Self::load_from_path(Self::DEFAULT_CONFIG_PATH)
@@ -189,6 +295,7 @@ impl PeopleConfiguration {
     /// Checks:
     /// - At least one person exists
     /// - No duplicate names
+    /// - Every person belongs to at least one group
     /// - All group references are valid
     /// - Each group has at least one active member
     ///
@@ -219,10 +326,36 @@ impl PeopleConfiguration {
 
         // Check all group references are valid
         for person in &self.people {
-            if !self.groups.contains_key(&person.group) {
-                return Err(ValidationError::UndefinedGroup {
-                    person: person.name.clone(),
-                    group: person.group.clone(),
+            if person.groups.is_empty() {
+                return Err(ValidationError::NoGroups(person.name.clone()));
+            }
+
+            for group in &person.groups {
+                if !self.groups.contains_key(group) {
+                    return Err(ValidationError::UndefinedGroup {
+                        person: person.name.clone(),
+                        group: group.clone(),
+                    });
+                }
+            }
+        }
+
+        // Check soft weights only refer to the group's own constraints and are at least 1
+        for (group_id, group) in &self.groups {
+            if let Some(constraint) = group
+                .soft_weights
+                .keys()
+                .find(|c| !group.constraints.contains(c))
+            {
+                return Err(ValidationError::UnknownSoftConstraint {
+                    group: group_id.clone(),
+                    constraint: constraint.clone(),
+                });
+            }
+            if let Some((constraint, _)) = group.soft_weights.iter().find(|(_, w)| **w == 0) {
+                return Err(ValidationError::ZeroSoftWeight {
+                    group: group_id.clone(),
+                    constraint: constraint.clone(),
                 });
             }
         }
@@ -232,7 +365,7 @@ impl PeopleConfiguration {
             let active_count = self
                 .people
                 .iter()
-                .filter(|p| p.group == *group_id && p.active)
+                .filter(|p| p.in_group(group_id) && p.active)
                 .count();
 
             if active_count == 0 {
@@ -254,7 +387,7 @@ impl PeopleConfiguration {
     ///
     /// Vector of references to people in the specified group
     pub fn get_people_by_group(&self, group: &str) -> Vec<&PersonConfig> {
-        self.people.iter().filter(|p| p.group == group).collect()
+        self.people.iter().filter(|p| p.in_group(group)).collect()
     }
 
     /// Get all active people (across all groups)
@@ -278,7 +411,7 @@ impl PeopleConfiguration {
     pub fn get_active_people_by_group(&self, group: &str) -> Vec<&PersonConfig> {
         self.people
             .iter()
-            .filter(|p| p.group == group && p.active)
+            .filter(|p| p.in_group(group) && p.active)
             .collect()
     }
 
@@ -295,6 +428,133 @@ impl PeopleConfiguration {
         self.groups.get(group)
     }
 
+    /// Get the constraints that apply to a person
+    ///
+    /// A person in several groups inherits the union of their groups'
+    /// constraints, so being in more groups never loosens restrictions.
+    ///
+    /// # Returns
+    ///
+    /// Sorted, de-duplicated constraint identifiers
+    pub fn constraints_for(&self, person: &PersonConfig) -> Vec<&String> {
+        let mut constraints: Vec<&String> = person
+            .groups
+            .iter()
+            .filter_map(|g| self.groups.get(g))
+            .flat_map(|g| g.constraints.iter())
+            .collect();
+        constraints.sort();
+        constraints.dedup();
+        constraints
+    }
+
+    /// Check whether any of a person's hard group constraints forbid a task
+    ///
+    /// Constraints of the form `cannot_perform_<task>` match the task name
+    /// lowercased with spaces replaced by underscores, so
+    /// `cannot_perform_toilet_b` forbids "Toilet B". Soft constraints never forbid.
+    pub fn forbids_task(&self, person: &PersonConfig, task: &str) -> bool {
+        let constraint = task_constraint(task);
+        person
+            .groups
+            .iter()
+            .filter_map(|g| self.groups.get(g))
+            .any(|g| g.hard_constraints().any(|c| *c == constraint))
+    }
+
+    /// Map each group to the given tasks its hard `cannot_perform_<task>` constraints forbid
+    ///
+    /// Every defined group gets an entry, empty if it may perform all of them.
+    pub fn task_exclusions<'a>(
+        &self,
+        tasks: impl IntoIterator<Item = &'a String>,
+    ) -> HashMap<String, Vec<String>> {
+        let tasks: Vec<&String> = tasks.into_iter().collect();
+        self.groups
+            .iter()
+            .map(|(id, group)| {
+                let mut excluded: Vec<String> = tasks
+                    .iter()
+                    .filter(|task| {
+                        let constraint = task_constraint(task);
+                        group.hard_constraints().any(|c| *c == constraint)
+                    })
+                    .map(|task| task.to_string())
+                    .collect();
+                excluded.sort();
+                (id.clone(), excluded)
+            })
+            .collect()
+    }
+
+    /// Map each active person to the given tasks their soft `cannot_perform_<task>`
+    /// constraints discourage, with the summed weight across their groups
+    ///
+    /// People without soft penalties are left out.
+    pub fn soft_penalties<'a>(
+        &self,
+        tasks: impl IntoIterator<Item = &'a String>,
+    ) -> HashMap<String, HashMap<String, u32>> {
+        let tasks: Vec<&String> = tasks.into_iter().collect();
+        let mut penalties: HashMap<String, HashMap<String, u32>> = HashMap::new();
+        for person in self.get_active_people() {
+            for group in person.groups.iter().filter_map(|g| self.groups.get(g)) {
+                for task in &tasks {
+                    let constraint = task_constraint(task);
+                    if !group.constraints.contains(&constraint) {
+                        continue;
+                    }
+                    if let Severity::Soft { weight } = group.severity(&constraint) {
+                        *penalties
+                            .entry(person.name.clone())
+                            .or_default()
+                            .entry(task.to_string())
+                            .or_default() += weight;
+                    }
+                }
+            }
+        }
+        penalties
+    }
+
+    /// Map each active person with hard-avoided tasks to those tasks
+    pub fn hard_avoids(&self) -> HashMap<String, Vec<String>> {
+        self.get_active_people()
+            .into_iter()
+            .filter(|p| !p.hard_avoid_tasks.is_empty())
+            .map(|p| (p.name.clone(), p.hard_avoid_tasks.clone()))
+            .collect()
+    }
+
+    /// Check every hard-avoided task names one of the given tasks
+    ///
+    /// Names are compared the way [`PersonConfig::avoids_task`] does, so a typo
+    /// fails loudly instead of silently excluding nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ValidationError::UnknownHardAvoidTask` for the first entry that
+    /// matches no task
+    pub fn check_hard_avoid_tasks<'a>(
+        &self,
+        tasks: impl IntoIterator<Item = &'a String>,
+    ) -> Result<(), ValidationError> {
+        let known: HashSet<String> = tasks.into_iter().map(|t| task_key(t)).collect();
+        for person in &self.people {
+            if let Some(task) = person
+                .hard_avoid_tasks
+                .iter()
+                .find(|t| !known.contains(&task_key(t)))
+            {
+                return Err(ValidationError::UnknownHardAvoidTask {
+                    person: person.name.clone(),
+                    task: task.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Get all group identifiers
     ///
     /// # Returns
@@ -341,6 +601,16 @@ impl PeopleConfiguration {
     }
 }
 
+/// Normalised task name used for matching, e.g. `toilet_b` for "Toilet B"
+pub fn task_key(task: &str) -> String {
+    task.to_lowercase().replace(' ', "_")
+}
+
+/// Constraint identifier forbidding a task, e.g. `cannot_perform_toilet_b` for "Toilet B"
+fn task_constraint(task: &str) -> String {
+    format!("cannot_perform_{}", task_key(task))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,6 +620,7 @@ mod tests {
         let group = GroupConfig {
             description: "Test group".to_string(),
             constraints: vec!["test_constraint".to_string()],
+            soft_weights: HashMap::new(),
         };
 
         let toml = toml::to_string(&group).unwrap();
@@ -367,6 +638,79 @@ mod tests {
 
         let person: PersonConfig = toml::from_str(toml).unwrap();
         assert!(person.active, "Active should default to true");
+        assert_eq!(person.groups, vec!["A".to_string()]);
+    }
+
+    #[test]
+    fn test_person_config_multiple_groups() {
+        let toml = r#"
+            name = "Floater"
+            groups = ["A", "B"]
+        "#;
+
+        let person: PersonConfig = toml::from_str(toml).unwrap();
+        assert!(person.in_group("A"));
+        assert!(person.in_group("B"));
+        assert!(!person.in_group("C"));
+    }
+
+    #[test]
+    fn test_person_config_hard_avoid_tasks() {
+        let toml = r#"
+            name = "Allergic"
+            group = "A"
+            hard_avoid_tasks = ["Bin"]
+        "#;
+
+        let person: PersonConfig = toml::from_str(toml).unwrap();
+        assert_eq!(person.hard_avoid_tasks, vec!["Bin".to_string()]);
+
+        let config = PeopleConfiguration {
+            groups: HashMap::new(),
+            people: vec![person],
+        };
+        assert_eq!(
+            config.hard_avoids().get("Allergic"),
+            Some(&vec!["Bin".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_avoids_task_ignores_case_and_spacing() {
+        let person = PersonConfig {
+            name: "Allergic".to_string(),
+            groups: vec!["A".to_string()],
+            active: true,
+            hard_avoid_tasks: vec!["bin".to_string(), "toilet_b".to_string()],
+        };
+
+        assert!(person.avoids_task("Bin"));
+        assert!(person.avoids_task("Toilet B"));
+        assert!(!person.avoids_task("Toilet A"));
+    }
+
+    #[test]
+    fn test_check_hard_avoid_tasks_rejects_unknown_task() {
+        let tasks = ["Bin".to_string(), "Toilet B".to_string()];
+        let mut config = PeopleConfiguration {
+            groups: HashMap::new(),
+            people: vec![PersonConfig {
+                name: "Allergic".to_string(),
+                groups: vec!["A".to_string()],
+                active: true,
+                hard_avoid_tasks: vec!["bin".to_string(), "Toilet B".to_string()],
+            }],
+        };
+        assert!(config.check_hard_avoid_tasks(&tasks).is_ok());
+
+        config.people[0].hard_avoid_tasks.push("Bins".to_string());
+        match config.check_hard_avoid_tasks(&tasks) {
+            Err(ValidationError::UnknownHardAvoidTask { person, task }) => {
+                assert_eq!(person, "Allergic");
+                assert_eq!(task, "Bins");
+            }
+            other => panic!("expected UnknownHardAvoidTask, got {:?}", other),
+        }
     }
 
     #[test]
@@ -391,6 +735,7 @@ mod tests {
             GroupConfig {
                 description: "Group A".to_string(),
                 constraints: vec![],
+                soft_weights: HashMap::new(),
             },
         );
 
@@ -399,13 +744,15 @@ mod tests {
             people: vec![
                 PersonConfig {
                     name: "John".to_string(),
-                    group: "A".to_string(),
+                    groups: vec!["A".to_string()],
                     active: true,
+                    hard_avoid_tasks: vec![],
                 },
                 PersonConfig {
                     name: "John".to_string(), // Duplicate!
-                    group: "A".to_string(),
+                    groups: vec!["A".to_string()],
                     active: true,
+                    hard_avoid_tasks: vec![],
                 },
             ],
         };
@@ -423,8 +770,9 @@ mod tests {
             groups: HashMap::new(), // No groups defined
             people: vec![PersonConfig {
                 name: "John".to_string(),
-                group: "A".to_string(), // References undefined group
+                groups: vec!["A".to_string()], // References undefined group
                 active: true,
+                hard_avoid_tasks: vec![],
             }],
         };
 
@@ -443,6 +791,7 @@ mod tests {
             GroupConfig {
                 description: "Group A".to_string(),
                 constraints: vec![],
+                soft_weights: HashMap::new(),
             },
         );
         groups.insert(
@@ -450,6 +799,7 @@ mod tests {
             GroupConfig {
                 description: "Group B".to_string(),
                 constraints: vec![],
+                soft_weights: HashMap::new(),
             },
         );
 
@@ -458,18 +808,21 @@ mod tests {
             people: vec![
                 PersonConfig {
                     name: "Alice".to_string(),
-                    group: "A".to_string(),
+                    groups: vec!["A".to_string()],
                     active: true,
+                    hard_avoid_tasks: vec![],
                 },
                 PersonConfig {
                     name: "Bob".to_string(),
-                    group: "B".to_string(),
+                    groups: vec!["B".to_string()],
                     active: true,
+                    hard_avoid_tasks: vec![],
                 },
                 PersonConfig {
                     name: "Charlie".to_string(),
-                    group: "A".to_string(),
+                    groups: vec!["A".to_string()],
                     active: true,
+                    hard_avoid_tasks: vec![],
                 },
             ],
         };
@@ -488,6 +841,7 @@ mod tests {
             GroupConfig {
                 description: "Group A".to_string(),
                 constraints: vec![],
+                soft_weights: HashMap::new(),
             },
         );
 
@@ -496,13 +850,15 @@ mod tests {
             people: vec![
                 PersonConfig {
                     name: "Active".to_string(),
-                    group: "A".to_string(),
+                    groups: vec!["A".to_string()],
                     active: true,
+                    hard_avoid_tasks: vec![],
                 },
                 PersonConfig {
                     name: "Inactive".to_string(),
-                    group: "A".to_string(),
+                    groups: vec!["A".to_string()],
                     active: false,
+                    hard_avoid_tasks: vec![],
                 },
             ],
         };
@@ -511,4 +867,222 @@ mod tests {
         assert_eq!(active.len(), 1);
         assert_eq!(active[0].name, "Active");
     }
+
+    #[test]
+    fn test_multi_group_person_in_both_filters() {
+        let mut groups = HashMap::new();
+        groups.insert(
+            "A".to_string(),
+            GroupConfig {
+                description: "Group A".to_string(),
+                constraints: vec!["cannot_perform_toilet_b".to_string()],
+                soft_weights: HashMap::new(),
+            },
+        );
+        groups.insert(
+            "B".to_string(),
+            GroupConfig {
+                description: "Group B".to_string(),
+                constraints: vec!["cannot_perform_toilet_a".to_string()],
+                soft_weights: HashMap::new(),
+            },
+        );
+
+        let config = PeopleConfiguration {
+            groups,
+            people: vec![
+                PersonConfig {
+                    name: "Alice".to_string(),
+                    groups: vec!["A".to_string()],
+                    active: true,
+                    hard_avoid_tasks: vec![],
+                },
+                PersonConfig {
+                    name: "Floater".to_string(),
+                    groups: vec!["A".to_string(), "B".to_string()],
+                    active: true,
+                    hard_avoid_tasks: vec![],
+                },
+            ],
+        };
+
+        assert!(config.validate().is_ok());
+
+        let group_a = config.get_active_people_by_group("A");
+        let group_b = config.get_active_people_by_group("B");
+        assert_eq!(group_a.len(), 2);
+        assert_eq!(group_b.len(), 1);
+        assert_eq!(group_b[0].name, "Floater");
+
+        let floater = config.find_person("Floater").unwrap();
+        assert_eq!(
+            config.constraints_for(floater),
+            vec!["cannot_perform_toilet_a", "cannot_perform_toilet_b"]
+        );
+    }
+
+    #[test]
+    fn test_forbids_task() {
+        let mut groups = HashMap::new();
+        groups.insert(
+            "B".to_string(),
+            GroupConfig {
+                description: "Group B".to_string(),
+                constraints: vec!["cannot_perform_toilet_a".to_string()],
+                soft_weights: HashMap::new(),
+            },
+        );
+        let person = PersonConfig {
+            name: "Bob".to_string(),
+            groups: vec!["B".to_string()],
+            active: true,
+            hard_avoid_tasks: vec![],
+        };
+        let config = PeopleConfiguration {
+            groups,
+            people: vec![person.clone()],
+        };
+
+        assert!(config.forbids_task(&person, "Toilet A"));
+        assert!(!config.forbids_task(&person, "Toilet B"));
+        assert!(!config.forbids_task(&person, "Parlor"));
+    }
+
+    #[test]
+    fn test_task_exclusions() {
+        let group = |constraints: &[&str]| GroupConfig {
+            description: String::new(),
+            constraints: constraints.iter().map(|c| c.to_string()).collect(),
+            soft_weights: HashMap::new(),
+        };
+        let config = PeopleConfiguration {
+            groups: HashMap::from([
+                ("A".to_string(), group(&["cannot_perform_toilet_b"])),
+                (
+                    "C".to_string(),
+                    group(&["cannot_perform_parlor", "cannot_perform_tank"]),
+                ),
+                ("D".to_string(), group(&[])),
+            ]),
+            people: vec![],
+        };
+        let tasks: Vec<String> = ["Parlor", "Tank", "Toilet B"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+
+        let exclusions = config.task_exclusions(&tasks);
+
+        assert_eq!(exclusions["A"], vec!["Toilet B".to_string()]);
+        assert_eq!(
+            exclusions["C"],
+            vec!["Parlor".to_string(), "Tank".to_string()]
+        );
+        assert!(exclusions["D"].is_empty());
+    }
+
+    #[test]
+    fn test_soft_constraint_severity() {
+        let toml = r#"
+            description = "Group B"
+            constraints = ["cannot_perform_toilet_a", "cannot_perform_tank"]
+            soft_weights = { cannot_perform_tank = 50 }
+        "#;
+        let group: GroupConfig = toml::from_str(toml).unwrap();
+
+        assert_eq!(group.severity("cannot_perform_toilet_a"), Severity::Hard);
+        assert_eq!(
+            group.severity("cannot_perform_tank"),
+            Severity::Soft { weight: 50 }
+        );
+
+        let person = PersonConfig {
+            name: "Bob".to_string(),
+            groups: vec!["B".to_string()],
+            active: true,
+            hard_avoid_tasks: vec![],
+        };
+        let config = PeopleConfiguration {
+            groups: HashMap::from([("B".to_string(), group)]),
+            people: vec![person.clone()],
+        };
+        let tasks: Vec<String> = ["Tank", "Toilet A"].iter().map(|t| t.to_string()).collect();
+
+        assert!(config.forbids_task(&person, "Toilet A"));
+        assert!(!config.forbids_task(&person, "Tank"));
+        assert_eq!(
+            config.task_exclusions(&tasks)["B"],
+            vec!["Toilet A".to_string()]
+        );
+        assert_eq!(config.soft_penalties(&tasks)["Bob"]["Tank"], 50);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validation_unknown_soft_constraint() {
+        let config = PeopleConfiguration {
+            groups: HashMap::from([(
+                "A".to_string(),
+                GroupConfig {
+                    description: String::new(),
+                    constraints: vec![],
+                    soft_weights: HashMap::from([("cannot_perform_tank".to_string(), 5)]),
+                },
+            )]),
+            people: vec![PersonConfig {
+                name: "Alice".to_string(),
+                groups: vec!["A".to_string()],
+                active: true,
+                hard_avoid_tasks: vec![],
+            }],
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ValidationError::UnknownSoftConstraint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validation_zero_soft_weight() {
+        let config = PeopleConfiguration {
+            groups: HashMap::from([(
+                "A".to_string(),
+                GroupConfig {
+                    description: String::new(),
+                    constraints: vec!["cannot_perform_tank".to_string()],
+                    soft_weights: HashMap::from([("cannot_perform_tank".to_string(), 0)]),
+                },
+            )]),
+            people: vec![PersonConfig {
+                name: "Alice".to_string(),
+                groups: vec!["A".to_string()],
+                active: true,
+                hard_avoid_tasks: vec![],
+            }],
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ValidationError::ZeroSoftWeight { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validation_no_groups() {
+        let config = PeopleConfiguration {
+            groups: HashMap::new(),
+            people: vec![PersonConfig {
+                name: "John".to_string(),
+                groups: vec![],
+                active: true,
+                hard_avoid_tasks: vec![],
+            }],
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ValidationError::NoGroups(_))
+        ));
+    }
 }
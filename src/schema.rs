@@ -6,6 +6,7 @@ diesel::table! {
         person_id -> Int4,
         task_name -> Text,
         assigned_at -> Timestamp,
+        run_id -> Nullable<Int4>,
     }
 }
 
@@ -18,6 +19,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    runs (id) {
+        id -> Int4,
+        created_at -> Timestamp,
+        attempts -> Int4,
+        seed -> Nullable<Int8>,
+        forced -> Bool,
+    }
+}
+
 diesel::joinable!(assignments -> people (person_id));
+diesel::joinable!(assignments -> runs (run_id));
 
-diesel::allow_tables_to_appear_in_same_query!(assignments, people,);
+diesel::allow_tables_to_appear_in_same_query!(assignments, people, runs,);
@@ -189,11 +189,11 @@ fn test_find_person() {
 
     let onel = config.find_person("Onel");
     assert!(onel.is_some(), "Should find person 'Onel'");
-    assert_eq!(onel.unwrap().group, "A", "Onel should be in Group A");
+    assert!(onel.unwrap().in_group("A"), "Onel should be in Group A");
 
     let romeo = config.find_person("Romeo");
     assert!(romeo.is_some(), "Should find person 'Romeo'");
-    assert_eq!(romeo.unwrap().group, "B", "Romeo should be in Group B");
+    assert!(romeo.unwrap().in_group("B"), "Romeo should be in Group B");
 
     let nonexistent = config.find_person("NonExistent");
     assert!(nonexistent.is_none(), "Should not find non-existent person");